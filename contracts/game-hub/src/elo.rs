@@ -0,0 +1,44 @@
+// Fixed-point Elo
+//
+// Ratings are kept in thousandths of a point and expected scores in parts per
+// million, so every update is integer arithmetic. The expected score
+// 1 / (1 + 10^(d / 400)) comes from a table sampled every 25 points of rating
+// difference and interpolated linearly in between. Differences beyond 800
+// points use the 800-point value.
+
+pub const RATING_SCALE: i64 = 1_000;
+pub const SCORE_SCALE: i64 = 1_000_000;
+pub const INITIAL_RATING: i64 = 1_500 * RATING_SCALE;
+
+const STEP: i64 = 25 * RATING_SCALE;
+
+// Expected score of the weaker side at 0, 25, ..., 800 points below its opponent
+const EXPECTED: [i64; 33] = [
+    500000, 464084, 428537, 393712, 359935, 327490, 296615, 267493, 240253, 214973, 191682,
+    170367, 150980, 133443, 117662, 103523, 90909, 79695, 69758, 60978, 53240, 46435, 40463,
+    35231, 30653, 26654, 23164, 20122, 17472, 15166, 13160, 11416, 9901,
+];
+
+// Expected score of `rating` against `opponent`, in parts per million
+pub fn expected_score(rating: i64, opponent: i64) -> i64 {
+    let diff = (opponent - rating).abs();
+    let max = STEP * (EXPECTED.len() as i64 - 1);
+    let weaker = if diff >= max {
+        EXPECTED[EXPECTED.len() - 1]
+    } else {
+        let i = (diff / STEP) as usize;
+        let offset = diff % STEP;
+        EXPECTED[i] - (EXPECTED[i] - EXPECTED[i + 1]) * offset / STEP
+    };
+    if opponent >= rating {
+        weaker
+    } else {
+        SCORE_SCALE - weaker
+    }
+}
+
+// Rating change for `rating` after a game against `opponent`, with K in whole points
+pub fn rating_change(k: u32, rating: i64, opponent: i64, won: bool) -> i64 {
+    let score = if won { SCORE_SCALE } else { 0 };
+    k as i64 * (score - expected_score(rating, opponent)) * RATING_SCALE / SCORE_SCALE
+}
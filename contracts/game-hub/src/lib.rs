@@ -0,0 +1,319 @@
+#![no_std]
+
+use soroban_sdk::{
+    contract, contractimpl, contracttype, log, symbol_short, vec, Address, Env,
+    Symbol, Vec,
+};
+
+mod elo;
+
+pub use elo::{INITIAL_RATING, RATING_SCALE};
+pub use zk_hub_interface::HubError;
+
+// ═══════════════════════════════════════════════════════════
+// ZeroWar Game Hub
+// Registered game contracts report the sessions they start and
+// the results they reach. The hub keeps every session, each
+// player's wins and losses, per game type and overall, and an
+// Elo rating per player per game type.
+// ═══════════════════════════════════════════════════════════
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DataKey {
+    Admin,
+    Games,
+    GameType(Address),
+    Session(u32),
+    Stats(Address),
+    StatsByType(Address, Symbol),
+    EloConfig,
+    Rating(Address, Symbol),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Session {
+    pub game: Address,
+    pub game_type: Symbol,
+    pub player1: Address,
+    pub player2: Address,
+    pub player1_points: i128,
+    pub player2_points: i128,
+    pub started_at: u32, // Ledger sequence
+    pub player1_won: Option<bool>, // Set once the game reports its result
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Record {
+    pub wins: u32,
+    pub losses: u32,
+}
+
+// A player is provisional for their first `provisional_games` games of a type
+// and moves by `k_provisional` points per game until then, `k_established` after
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct EloConfig {
+    pub k_provisional: u32,
+    pub k_established: u32,
+    pub provisional_games: u32,
+}
+
+impl EloConfig {
+    pub fn standard() -> Self {
+        EloConfig {
+            k_provisional: 40,
+            k_established: 20,
+            provisional_games: 30,
+        }
+    }
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Rating {
+    pub rating: i64, // Thousandths of a point, so 1500 is 1_500_000
+    pub games: u32,
+    pub provisional: bool,
+}
+
+const MAX_K: u32 = 100;
+
+#[contract]
+pub struct GameHub;
+
+#[contractimpl]
+impl GameHub {
+    pub fn init(env: Env, admin: Address) -> Result<(), HubError> {
+        if env.storage().persistent().has(&DataKey::Admin) {
+            return Err(HubError::AlreadyInitialized);
+        }
+        admin.require_auth();
+        env.storage().persistent().set(&DataKey::Admin, &admin);
+        Ok(())
+    }
+
+    // ─── Register a game contract ───
+    // `game_type` groups results across deployments of the same game,
+    // e.g. `battleship` or `tcg`
+    pub fn register_game(env: Env, game: Address, game_type: Symbol) -> Result<(), HubError> {
+        Self::require_admin(&env)?;
+
+        let type_key = DataKey::GameType(game.clone());
+        if env.storage().persistent().has(&type_key) {
+            return Err(HubError::GameAlreadyRegistered);
+        }
+        env.storage().persistent().set(&type_key, &game_type);
+        let mut games = Self::get_games(env.clone());
+        games.push_back(game.clone());
+        env.storage().persistent().set(&DataKey::Games, &games);
+
+        env.events().publish(
+            (symbol_short!("game"), symbol_short!("register")),
+            (game, game_type),
+        );
+        Ok(())
+    }
+
+    // ─── Elo settings ───
+    // Each K-factor must be between 1 and MAX_K points
+    pub fn set_elo_config(env: Env, config: EloConfig) -> Result<(), HubError> {
+        Self::require_admin(&env)?;
+        for k in [config.k_provisional, config.k_established] {
+            if k == 0 || k > MAX_K {
+                return Err(HubError::InvalidEloConfig);
+            }
+        }
+        env.storage().persistent().set(&DataKey::EloConfig, &config);
+        Ok(())
+    }
+
+    // ─── Record a session start ───
+    // Called by the game contract itself. Session ids are global across
+    // games, so games should draw them at random rather than count them.
+    pub fn start_game(
+        env: Env,
+        game_id: Address,
+        session_id: u32,
+        player1: Address,
+        player2: Address,
+        player1_points: i128,
+        player2_points: i128,
+    ) -> Result<(), HubError> {
+        game_id.require_auth();
+
+        let game_type = Self::get_game_type(env.clone(), game_id.clone())
+            .ok_or(HubError::GameNotRegistered)?;
+        if player1 == player2 {
+            return Err(HubError::InvalidPlayer);
+        }
+        let key = DataKey::Session(session_id);
+        if env.storage().persistent().has(&key) {
+            return Err(HubError::SessionExists);
+        }
+
+        let session = Session {
+            game: game_id.clone(),
+            game_type,
+            player1,
+            player2,
+            player1_points,
+            player2_points,
+            started_at: env.ledger().sequence(),
+            player1_won: None,
+        };
+        env.storage().persistent().set(&key, &session);
+
+        log!(&env, "Session started: session={}", session_id);
+        env.events().publish(
+            (symbol_short!("session"), symbol_short!("start")),
+            (session_id, game_id),
+        );
+        Ok(())
+    }
+
+    // ─── Record a session result ───
+    // Only the game that started the session can end it, once
+    pub fn end_game(env: Env, session_id: u32, player1_won: bool) -> Result<(), HubError> {
+        let key = DataKey::Session(session_id);
+        let mut session: Session = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .ok_or(HubError::SessionNotFound)?;
+        session.game.require_auth();
+        if session.player1_won.is_some() {
+            return Err(HubError::SessionEnded);
+        }
+
+        session.player1_won = Some(player1_won);
+        env.storage().persistent().set(&key, &session);
+
+        let (winner, loser) = if player1_won {
+            (&session.player1, &session.player2)
+        } else {
+            (&session.player2, &session.player1)
+        };
+        Self::add_result(&env, winner, &session.game_type, true);
+        Self::add_result(&env, loser, &session.game_type, false);
+        Self::update_ratings(&env, winner, loser, &session.game_type);
+
+        env.events().publish(
+            (symbol_short!("session"), symbol_short!("end")),
+            (session_id, winner.clone()),
+        );
+        Ok(())
+    }
+
+    // ─── View functions ───
+
+    pub fn get_games(env: Env) -> Vec<Address> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Games)
+            .unwrap_or(vec![&env])
+    }
+
+    pub fn get_game_type(env: Env, game: Address) -> Option<Symbol> {
+        env.storage().persistent().get(&DataKey::GameType(game))
+    }
+
+    pub fn get_session(env: Env, session_id: u32) -> Option<Session> {
+        env.storage().persistent().get(&DataKey::Session(session_id))
+    }
+
+    // Wins and losses across every game type
+    pub fn get_record(env: Env, player: Address) -> Record {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Stats(player))
+            .unwrap_or_default()
+    }
+
+    pub fn get_record_for(env: Env, player: Address, game_type: Symbol) -> Record {
+        env.storage()
+            .persistent()
+            .get(&DataKey::StatsByType(player, game_type))
+            .unwrap_or_default()
+    }
+
+    pub fn get_elo_config(env: Env) -> EloConfig {
+        env.storage()
+            .persistent()
+            .get(&DataKey::EloConfig)
+            .unwrap_or(EloConfig::standard())
+    }
+
+    // `player`'s rating for one game type; unrated players start at INITIAL_RATING
+    pub fn get_rating(env: Env, player: Address, game_type: Symbol) -> Rating {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Rating(player, game_type))
+            .unwrap_or(Rating {
+                rating: INITIAL_RATING,
+                games: 0,
+                provisional: true,
+            })
+    }
+
+    // ─── Internal ───
+
+    fn require_admin(env: &Env) -> Result<(), HubError> {
+        let admin: Address = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Admin)
+            .ok_or(HubError::NotInitialized)?;
+        admin.require_auth();
+        Ok(())
+    }
+
+    // Both changes are computed from the ratings before the game
+    fn update_ratings(env: &Env, winner: &Address, loser: &Address, game_type: &Symbol) {
+        let config = Self::get_elo_config(env.clone());
+        let before = [
+            Self::get_rating(env.clone(), winner.clone(), game_type.clone()),
+            Self::get_rating(env.clone(), loser.clone(), game_type.clone()),
+        ];
+        for (i, player) in [winner, loser].into_iter().enumerate() {
+            let (mine, theirs) = (&before[i], &before[1 - i]);
+            let k = if mine.provisional {
+                config.k_provisional
+            } else {
+                config.k_established
+            };
+            let games = mine.games + 1;
+            let rating = Rating {
+                rating: mine.rating + elo::rating_change(k, mine.rating, theirs.rating, i == 0),
+                games,
+                provisional: games < config.provisional_games,
+            };
+            env.storage()
+                .persistent()
+                .set(&DataKey::Rating(player.clone(), game_type.clone()), &rating);
+            env.events().publish(
+                (symbol_short!("rating"), game_type.clone()),
+                (player.clone(), rating.rating),
+            );
+        }
+    }
+
+    fn add_result(env: &Env, player: &Address, game_type: &Symbol, won: bool) {
+        for key in [
+            DataKey::Stats(player.clone()),
+            DataKey::StatsByType(player.clone(), game_type.clone()),
+        ] {
+            let mut record: Record = env.storage().persistent().get(&key).unwrap_or_default();
+            if won {
+                record.wins += 1;
+            } else {
+                record.losses += 1;
+            }
+            env.storage().persistent().set(&key, &record);
+        }
+    }
+}
+
+mod test;
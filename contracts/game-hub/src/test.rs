@@ -0,0 +1,204 @@
+#![cfg(test)]
+
+use soroban_sdk::{
+    symbol_short,
+    testutils::{Address as _, Events},
+    vec, Address, Env, IntoVal, Symbol,
+};
+
+use crate::elo::{expected_score, rating_change};
+use crate::{EloConfig, GameHub, GameHubClient, HubError, Rating, Record, INITIAL_RATING, RATING_SCALE};
+
+fn setup(env: &Env) -> (GameHubClient<'_>, Address, Address) {
+    env.mock_all_auths();
+    let client = GameHubClient::new(env, &env.register(GameHub, ()));
+    client.init(&Address::generate(env));
+
+    let battleship = Address::generate(env);
+    let tcg = Address::generate(env);
+    client.register_game(&battleship, &symbol_short!("battle"));
+    client.register_game(&tcg, &symbol_short!("tcg"));
+    (client, battleship, tcg)
+}
+
+#[test]
+fn test_sessions_record_start_and_result() {
+    let env = Env::default();
+    let (client, battleship, _) = setup(&env);
+    let (alice, bob) = (Address::generate(&env), Address::generate(&env));
+
+    client.start_game(&battleship, &7, &alice, &bob, &100, &100);
+    let session = client.get_session(&7).unwrap();
+    assert_eq!(session.game, battleship);
+    assert_eq!(session.game_type, symbol_short!("battle"));
+    assert_eq!(session.player1_won, None);
+
+    client.end_game(&7, &false);
+
+    let end_event = (
+        client.address.clone(),
+        (symbol_short!("session"), symbol_short!("end")).into_val(&env),
+        (7u32, bob.clone()).into_val(&env),
+    );
+    assert!(env.events().all().contains(&end_event));
+    assert_eq!(client.get_session(&7).unwrap().player1_won, Some(false));
+    assert_eq!(client.get_record(&bob), Record { wins: 1, losses: 0 });
+    assert_eq!(client.get_record(&alice), Record { wins: 0, losses: 1 });
+}
+
+#[test]
+fn test_records_add_up_across_game_types() {
+    let env = Env::default();
+    let (client, battleship, tcg) = setup(&env);
+    let (alice, bob) = (Address::generate(&env), Address::generate(&env));
+
+    client.start_game(&battleship, &1, &alice, &bob, &0, &0);
+    client.start_game(&tcg, &2, &bob, &alice, &0, &0);
+    client.start_game(&tcg, &3, &alice, &bob, &0, &0);
+    client.end_game(&1, &true);
+    client.end_game(&2, &false);
+    client.end_game(&3, &false);
+
+    assert_eq!(client.get_record(&alice), Record { wins: 2, losses: 1 });
+    assert_eq!(client.get_record_for(&alice, &symbol_short!("battle")), Record { wins: 1, losses: 0 });
+    assert_eq!(client.get_record_for(&alice, &symbol_short!("tcg")), Record { wins: 1, losses: 1 });
+    assert_eq!(client.get_record_for(&bob, &Symbol::new(&env, "chess")), Record::default());
+}
+
+#[test]
+fn test_only_registered_games_start_sessions() {
+    let env = Env::default();
+    let (client, battleship, tcg) = setup(&env);
+    let (alice, bob) = (Address::generate(&env), Address::generate(&env));
+    let stranger = Address::generate(&env);
+
+    assert_eq!(
+        client.try_start_game(&stranger, &1, &alice, &bob, &0, &0),
+        Err(Ok(HubError::GameNotRegistered))
+    );
+    assert_eq!(
+        client.try_start_game(&battleship, &1, &alice, &alice, &0, &0),
+        Err(Ok(HubError::InvalidPlayer))
+    );
+    assert_eq!(
+        client.try_register_game(&battleship, &symbol_short!("tcg")),
+        Err(Ok(HubError::GameAlreadyRegistered))
+    );
+    assert_eq!(client.get_games(), vec![&env, battleship, tcg]);
+}
+
+#[test]
+fn test_sessions_start_and_end_once() {
+    let env = Env::default();
+    let (client, battleship, tcg) = setup(&env);
+    let (alice, bob) = (Address::generate(&env), Address::generate(&env));
+
+    client.start_game(&battleship, &1, &alice, &bob, &0, &0);
+    assert_eq!(
+        client.try_start_game(&tcg, &1, &alice, &bob, &0, &0),
+        Err(Ok(HubError::SessionExists))
+    );
+    assert_eq!(client.try_end_game(&2, &true), Err(Ok(HubError::SessionNotFound)));
+
+    client.end_game(&1, &true);
+    assert_eq!(client.try_end_game(&1, &false), Err(Ok(HubError::SessionEnded)));
+    assert_eq!(client.get_record(&alice), Record { wins: 1, losses: 0 });
+}
+
+#[test]
+fn test_results_come_from_the_session_game() {
+    let env = Env::default();
+    let (client, battleship, _) = setup(&env);
+    let (alice, bob) = (Address::generate(&env), Address::generate(&env));
+    client.start_game(&battleship, &1, &alice, &bob, &0, &0);
+
+    client.end_game(&1, &true);
+
+    assert_eq!(env.auths()[0].0, battleship);
+}
+
+#[test]
+fn test_init_runs_once() {
+    let env = Env::default();
+    let (client, _, _) = setup(&env);
+
+    assert_eq!(client.try_init(&Address::generate(&env)), Err(Ok(HubError::AlreadyInitialized)));
+    assert_eq!(client.get_game_type(&Address::generate(&env)), None);
+}
+
+fn points(rating: i64) -> i64 {
+    rating * RATING_SCALE
+}
+
+#[test]
+fn test_expected_score_follows_the_elo_curve() {
+    assert_eq!(expected_score(points(1500), points(1500)), 500_000);
+    // 400 points apart: 1 / 11 and 10 / 11
+    assert_eq!(expected_score(points(1500), points(1900)), 90_909);
+    assert_eq!(expected_score(points(1900), points(1500)), 909_091);
+    // Halfway between the 0 and 25 point samples
+    assert_eq!(expected_score(points(1500), points(1500) + 12_500), 482_042);
+    // Capped at 800 points
+    assert_eq!(expected_score(points(1000), points(2500)), 9_901);
+}
+
+#[test]
+fn test_rating_change_is_k_times_the_surprise() {
+    assert_eq!(rating_change(32, points(1500), points(1500), true), points(16));
+    assert_eq!(rating_change(32, points(1500), points(1500), false), -points(16));
+    // An upset pays K * 10 / 11
+    assert_eq!(rating_change(20, points(1500), points(1900), true), 18_181);
+    assert_eq!(rating_change(20, points(1900), points(1500), false), -18_181);
+}
+
+#[test]
+fn test_results_update_ratings_per_game_type() {
+    let env = Env::default();
+    let (client, battleship, _) = setup(&env);
+    let (alice, bob) = (Address::generate(&env), Address::generate(&env));
+    client.set_elo_config(&EloConfig { k_provisional: 40, k_established: 20, provisional_games: 1 });
+
+    client.start_game(&battleship, &1, &alice, &bob, &0, &0);
+    client.end_game(&1, &true);
+
+    // Both provisional at 1500: K = 40 moves each side by 20
+    let battle = symbol_short!("battle");
+    assert_eq!(
+        client.get_rating(&alice, &battle),
+        Rating { rating: points(1520), games: 1, provisional: false }
+    );
+    assert_eq!(client.get_rating(&bob, &battle).rating, points(1480));
+    assert_eq!(client.get_rating(&alice, &symbol_short!("tcg")).rating, INITIAL_RATING);
+
+    // Established now: K = 20, and alice is expected to win 0.442756 of the time
+    client.start_game(&battleship, &2, &bob, &alice, &0, &0);
+    client.end_game(&2, &true);
+    assert_eq!(client.get_rating(&bob, &battle).rating, points(1480) + 11_144);
+    assert_eq!(client.get_rating(&alice, &battle).rating, points(1520) - 11_144);
+}
+
+#[test]
+fn test_new_players_stay_provisional() {
+    let env = Env::default();
+    let (client, battleship, _) = setup(&env);
+    let (alice, bob) = (Address::generate(&env), Address::generate(&env));
+    assert_eq!(client.get_elo_config(), EloConfig::standard());
+    assert!(client.get_rating(&alice, &symbol_short!("battle")).provisional);
+
+    client.start_game(&battleship, &1, &alice, &bob, &0, &0);
+    client.end_game(&1, &false);
+
+    let rating = client.get_rating(&alice, &symbol_short!("battle"));
+    assert_eq!((rating.rating, rating.games, rating.provisional), (points(1480), 1, true));
+}
+
+#[test]
+fn test_elo_config_bounds_the_k_factors() {
+    let env = Env::default();
+    let (client, _, _) = setup(&env);
+
+    for (k_provisional, k_established) in [(0, 20), (40, 0), (101, 20)] {
+        let config = EloConfig { k_provisional, k_established, provisional_games: 30 };
+        assert_eq!(client.try_set_elo_config(&config), Err(Ok(HubError::InvalidEloConfig)));
+    }
+}
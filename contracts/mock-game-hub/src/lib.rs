@@ -0,0 +1,92 @@
+#![no_std]
+
+// Stand-in for the Game Hub, so the games' hub calls run under `cargo test`.
+// It records every session it is told about, rejects a session id it already
+// holds the way the real hub does, and can be switched into a failing mode to
+// check that a broken hub never blocks a game.
+
+use soroban_sdk::{contract, contractimpl, contracttype, Address, Env};
+use zk_hub_interface::HubError;
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum DataKey {
+    Failing,
+    Session(u32),
+}
+
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Session {
+    pub game_id: Address,
+    pub player1: Address,
+    pub player2: Address,
+    pub player1_points: i128,
+    pub player2_points: i128,
+    pub player1_won: Option<bool>, // Set once the game reports its result
+}
+
+#[contract]
+pub struct MockGameHub;
+
+#[contractimpl]
+impl MockGameHub {
+    pub fn start_game(
+        env: Env,
+        game_id: Address,
+        session_id: u32,
+        player1: Address,
+        player2: Address,
+        player1_points: i128,
+        player2_points: i128,
+    ) -> Result<(), HubError> {
+        Self::check_available(&env);
+        if env.storage().persistent().has(&DataKey::Session(session_id)) {
+            return Err(HubError::SessionExists);
+        }
+        let session = Session {
+            game_id,
+            player1,
+            player2,
+            player1_points,
+            player2_points,
+            player1_won: None,
+        };
+        env.storage()
+            .persistent()
+            .set(&DataKey::Session(session_id), &session);
+        Ok(())
+    }
+
+    pub fn end_game(env: Env, session_id: u32, player1_won: bool) {
+        Self::check_available(&env);
+        let key = DataKey::Session(session_id);
+        let mut session: Session = env
+            .storage()
+            .persistent()
+            .get(&key)
+            .expect("unknown session");
+        session.player1_won = Some(player1_won);
+        env.storage().persistent().set(&key, &session);
+    }
+
+    // While set, every hub call panics
+    pub fn set_failing(env: Env, failing: bool) {
+        env.storage().persistent().set(&DataKey::Failing, &failing);
+    }
+
+    pub fn get_session(env: Env, session_id: u32) -> Option<Session> {
+        env.storage().persistent().get(&DataKey::Session(session_id))
+    }
+
+    fn check_available(env: &Env) {
+        if env
+            .storage()
+            .persistent()
+            .get::<_, bool>(&DataKey::Failing)
+            .unwrap_or(false)
+        {
+            panic!("hub unavailable");
+        }
+    }
+}
@@ -1,17 +1,54 @@
 #![no_std]
 
+use core::cmp::Ordering;
 use soroban_sdk::{
-    contract, contracterror, contractimpl, contracttype,
+    contract, contractclient, contracterror, contractimpl, contracttype,
     crypto::bls12_381::{Fr, G1Affine, G2Affine},
-    log, symbol_short, vec, Address, BytesN, Env, IntoVal, Symbol, Vec,
+    log, symbol_short, token, vec, Address, BytesN, Env, Symbol, Vec, U256,
 };
+use zk_hub_interface::HubError;
+use zk_poseidon2 as poseidon2;
 
 // ═══════════════════════════════════════════════════════════
 // Game Hub Contract Interface
-// Cross-contract calls use env.invoke_contract() with the
-// Game Hub address: CB4VZAT2U3UC6XFK3N23SKRF2NDCMP3QHJYMCHHFMZO7MRQO6DQ2EMYG
+// Games report sessions to the hub set with `set_game_hub`
+// (Testnet: CB4VZAT2U3UC6XFK3N23SKRF2NDCMP3QHJYMCHHFMZO7MRQO6DQ2EMYG).
+// Calls go through the `try_` client methods, so a failing hub
+// never blocks a game.
 // ═══════════════════════════════════════════════════════════
 
+#[contractclient(name = "GameHubClient")]
+pub trait GameHub {
+    fn start_game(
+        env: Env,
+        game_id: Address,
+        session_id: u32,
+        player1: Address,
+        player2: Address,
+        player1_points: i128,
+        player2_points: i128,
+    ) -> Result<(), HubError>;
+
+    fn end_game(env: Env, session_id: u32, player1_won: bool);
+
+    // Views served by contracts/game-hub, used for rating-band matchmaking
+    fn get_game_type(env: Env, game: Address) -> Option<Symbol>;
+
+    fn get_rating(env: Env, player: Address, game_type: Symbol) -> HubRating;
+}
+
+// Session ids drawn before giving up on a hub whose ids keep colliding
+const SESSION_ATTEMPTS: u32 = 4;
+
+// A player's rating as the hub reports it, in thousandths of a point
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct HubRating {
+    pub rating: i64,
+    pub games: u32,
+    pub provisional: bool,
+}
+
 // ═══════════════════════════════════════════════════════════
 // Data Types
 // ═══════════════════════════════════════════════════════════
@@ -29,21 +66,198 @@ pub enum BattleshipError {
     GameAlreadyEnded = 7,
     NotAllBoardsCommitted = 8,
     MalformedProof = 9,
+    NotInitialized = 10,
+    GameNotEnded = 11,
+    BoardAlreadyRevealed = 12,
+    InvalidBoard = 13,
+    CommitmentMismatch = 14,
+    CellAlreadyShot = 15,
+    InvalidShotResult = 16,
+    InvalidFleet = 17,
+    WrongGameMode = 18,
+    InvalidSalvo = 19,
+    BatchTooLarge = 20,
+    InvalidWager = 21,
+    TimeoutNotReached = 22,
+    AwaitingYourMove = 23,
+    AlreadyInitialized = 24,
+    FeeTooHigh = 25,
+    ChallengeNotFound = 26,
+    OutsideRatingBand = 27,
+    RatingUnavailable = 28,
+    InviteExpired = 29,
+    NotYourTurn = 30,
+    NoCalledShot = 31,
+    TooManyChallenges = 32,
 }
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum DataKey {
-    Player1,
-    Player2,
-    BoardHash(Address),
-    GameStarted,
-    GameEnded,
-    HitsCount(Address),
-    SessionId,
-    Winner,
+    Admin,
+    GameHub,
+    Treasury,
+    FeeBps,
+    Fees(Address), // Fees collected and not yet withdrawn, per token
+    GameCounter,
+    Game(u32),
     VerificationKey,
-    TotalShots,
+    PlacementKey,
+    Fleet,
+    ShotsAt(u32, Address),
+    RevealedBoard(u32, Address),
+    SalvoKey(u32),
+    CancelRequest(u32, Address),
+    ChallengeCounter,
+    Challenge(u32),
+    OpenCount,              // Challenges waiting for an opponent
+    OpenSlot(u32),          // Challenge id at each position of the open list
+    OpenByCreator(Address), // Challenges a creator has waiting
+}
+
+// How many shots a player fires per salvo
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SalvoSize {
+    Fixed(u32),
+    SurvivingShips, // One shot per ship the shooter still has afloat
+}
+
+// Stake each player puts into escrow, in units of a Stellar Asset Contract token
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Wager {
+    pub token: Address,
+    pub stake: i128,
+}
+
+// Rules a challenge is played under
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GameMode {
+    Classic,
+    Salvo(SalvoSize),
+}
+
+// A game offered in the lobby, waiting for an opponent
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Challenge {
+    pub id: u32,
+    pub creator: Address,
+    pub opponent: Option<Address>, // Only this player may accept, if set
+    pub mode: GameMode,
+    pub token: Option<Address>, // Wager token, None for an unstaked game
+    pub stake: i128,
+    pub rating_band: Option<u32>, // Largest Game Hub rating gap accepted, in whole points
+    pub created_at: u32,
+    pub slot: u32, // Position in the open list; moves when another challenge closes
+}
+
+#[contracttype]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GameStatus {
+    Invited, // Waiting for player2 to accept
+    Active,
+    Ended,
+    Withdrawn, // The invite was cancelled or declined before play started
+}
+
+// One player's side of a game
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PlayerState {
+    pub address: Address,
+    pub board: Option<BytesN<32>>, // Board commitment, once committed
+    pub hits: u32,                 // Hits this player has landed
+    pub sunk: Vec<u32>,            // Ids of this player's ships sunk, in the order they went down
+    pub deposit: i128,             // Stake held in escrow for this player
+    pub cheater: bool,             // Failed the post-game audit
+}
+
+// Everything a move reads or writes, kept in one entry so each call loads and
+// stores the game once. Shot logs and revealed boards keep their own entries.
+#[contracttype]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GameState {
+    pub player1: PlayerState,
+    pub player2: PlayerState,
+    pub mode: GameMode,
+    pub status: GameStatus,
+    pub total_shots: u32,
+    pub on_clock: Option<Address>, // Who owes the next action; None until both boards are committed
+    pub clock_start: u32,
+    pub called: Vec<u32>, // Cells called with `call_shots` that the player on the clock must answer
+    pub winner: Option<Address>,
+    pub invite_deadline: u32,   // Last ledger player2 can accept the invite on
+    pub token: Option<Address>, // Wager token, None for an unstaked game
+    pub stake: i128,
+    pub fee_bps: u32, // Protocol fee in force when the game was created
+    pub hub_session: Option<u32>,
+}
+
+impl GameState {
+    fn player(&self, address: &Address) -> Result<&PlayerState, BattleshipError> {
+        if *address == self.player1.address {
+            Ok(&self.player1)
+        } else if *address == self.player2.address {
+            Ok(&self.player2)
+        } else {
+            Err(BattleshipError::InvalidPlayer)
+        }
+    }
+
+    // `address`'s side and their opponent's
+    fn sides_mut(
+        &mut self,
+        address: &Address,
+    ) -> Result<(&mut PlayerState, &mut PlayerState), BattleshipError> {
+        if *address == self.player1.address {
+            Ok((&mut self.player1, &mut self.player2))
+        } else if *address == self.player2.address {
+            Ok((&mut self.player2, &mut self.player1))
+        } else {
+            Err(BattleshipError::InvalidPlayer)
+        }
+    }
+
+    fn opponent(&self, address: &Address) -> Result<&PlayerState, BattleshipError> {
+        self.player(address)?;
+        if *address == self.player1.address {
+            Ok(&self.player2)
+        } else {
+            Ok(&self.player1)
+        }
+    }
+
+    fn require_active(&self) -> Result<(), BattleshipError> {
+        match self.status {
+            GameStatus::Active => Ok(()),
+            GameStatus::Ended => Err(BattleshipError::GameAlreadyEnded),
+            GameStatus::Invited | GameStatus::Withdrawn => Err(BattleshipError::GameNotStarted),
+        }
+    }
+
+    fn is_over(&self) -> bool {
+        matches!(self.status, GameStatus::Ended | GameStatus::Withdrawn)
+    }
+
+    fn wager(&self) -> Option<Wager> {
+        self.token.clone().map(|token| Wager {
+            token,
+            stake: self.stake,
+        })
+    }
+}
+
+// A shot fired at a player's board and the result claimed for it,
+// kept so the board can be audited once it is revealed
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Shot {
+    pub index: u32,
+    pub result: u32,
+    pub sunk: u32, // Id of the ship this hit completed, 0 if none
 }
 
 // Groth16 proof structures (BLS12-381 based)
@@ -72,363 +286,1659 @@ pub struct Groth16Proof {
 #[contract]
 pub struct ZkBattleship;
 
-// Game Hub contract address on Testnet
-const GAME_HUB_ADDRESS: &str = "CB4VZAT2U3UC6XFK3N23SKRF2NDCMP3QHJYMCHHFMZO7MRQO6DQ2EMYG";
-const MAX_HITS_TO_WIN: u32 = 5; // Number of ship cells to sink all ships
+const GRID_SIZE: u32 = 5;
+const BOARD_CELLS: u32 = GRID_SIZE * GRID_SIZE;
+const MAX_SHIPS: u32 = 5;
+const MAX_SALVO: u32 = MAX_SHIPS;
+// Largest batch `settle_shots` verifies within one transaction's CPU budget
+const MAX_BATCH: u32 = 4;
+// Ledgers a player has to answer before the opponent can claim the win
+const MOVE_TIMEOUT: u32 = 120;
+// Protocol fee cap, in basis points of the pot
+const MAX_FEE_BPS: u32 = 1_000;
+const BPS: i128 = 10_000;
+// About a day of 5-second ledgers
+const DAY_IN_LEDGERS: u32 = 17_280;
+// Ledgers player2 has to accept an invite
+const INVITE_TIMEOUT: u32 = DAY_IN_LEDGERS;
+// Storage lifetimes: the instance and every game entry are extended back to
+// 30 days whenever they drop below 29, so an active game never gets archived.
+const TTL_BUMP: u32 = 30 * DAY_IN_LEDGERS;
+const TTL_THRESHOLD: u32 = TTL_BUMP - DAY_IN_LEDGERS;
+// Ledgers an unanswered cancel request lives in temporary storage
+const REQUEST_TTL: u32 = DAY_IN_LEDGERS;
+// Most challenges returned by one list_open_challenges call
+const MAX_PAGE: u32 = 20;
+// Challenges one creator can have waiting at a time
+const MAX_OPEN_PER_CREATOR: u32 = 5;
+// Hub ratings are in thousandths of a point
+const RATING_SCALE: i64 = 1_000;
 
 #[contractimpl]
 impl ZkBattleship {
-    // ─── Initialize the verification key ───
-    pub fn init(env: Env, vk: VerificationKey) {
-        env.storage().persistent().set(&DataKey::VerificationKey, &vk);
+    // ─── Initialize the verification keys and fleet ───
+    // `vk` checks shot proofs, `placement_vk` checks fleet proofs at commit_board.
+    // `fleet` lists ship lengths by id: ship `i + 1` is `fleet[i]` cells long.
+    // Boards hold the id of the ship on each cell, or 0 for water.
+    // `admin` sets the protocol fee and starts out as the fee treasury.
+    pub fn init(
+        env: Env,
+        admin: Address,
+        vk: VerificationKey,
+        placement_vk: VerificationKey,
+        fleet: Vec<u32>,
+    ) -> Result<(), BattleshipError> {
+        if env.storage().instance().has(&DataKey::Admin) {
+            return Err(BattleshipError::AlreadyInitialized);
+        }
+        admin.require_auth();
+        if fleet.is_empty()
+            || fleet.len() > MAX_SHIPS
+            || fleet.iter().any(|length| length == 0 || length > GRID_SIZE)
+        {
+            return Err(BattleshipError::InvalidFleet);
+        }
+
+        env.storage().instance().set(&DataKey::VerificationKey, &vk);
+        env.storage()
+            .instance()
+            .set(&DataKey::PlacementKey, &placement_vk);
+        env.storage().instance().set(&DataKey::Fleet, &fleet);
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(&DataKey::Treasury, &admin);
+        Self::extend_instance(&env);
+        Ok(())
+    }
+
+    // ─── Protocol fee ───
+    // Basis points taken from the pot of every wagered game that ends with a
+    // result, capped at MAX_FEE_BPS. A game keeps the fee in force when it started.
+    pub fn set_fee(env: Env, fee_bps: u32) -> Result<(), BattleshipError> {
+        Self::require_admin(&env)?;
+        if fee_bps > MAX_FEE_BPS {
+            return Err(BattleshipError::FeeTooHigh);
+        }
+        env.storage().instance().set(&DataKey::FeeBps, &fee_bps);
+        env.events()
+            .publish((symbol_short!("fee"), symbol_short!("set")), fee_bps);
+        Ok(())
+    }
+
+    // ─── Game Hub ───
+    // Sessions start and end are reported here from then on
+    pub fn set_game_hub(env: Env, hub: Address) -> Result<(), BattleshipError> {
+        Self::require_admin(&env)?;
+        env.storage().instance().set(&DataKey::GameHub, &hub);
+        Ok(())
+    }
+
+    pub fn get_game_hub(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::GameHub)
+    }
+
+    // Session id the game was registered under with the Game Hub
+    pub fn get_hub_session(env: Env, game_id: u32) -> Option<u32> {
+        Self::get_game(env, game_id).and_then(|state| state.hub_session)
+    }
+
+    pub fn set_treasury(env: Env, treasury: Address) -> Result<(), BattleshipError> {
+        Self::require_admin(&env)?;
+        env.storage().instance().set(&DataKey::Treasury, &treasury);
+        env.events()
+            .publish((symbol_short!("fee"), symbol_short!("treasury")), treasury);
+        Ok(())
+    }
+
+    // Sends the fees collected in `token` to the treasury and returns the amount
+    pub fn withdraw_fees(env: Env, token: Address) -> Result<i128, BattleshipError> {
+        Self::require_admin(&env)?;
+        let treasury: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Treasury)
+            .ok_or(BattleshipError::NotInitialized)?;
+        let amount = Self::get_fees(env.clone(), token.clone());
+        if amount > 0 {
+            env.storage()
+                .persistent()
+                .remove(&DataKey::Fees(token.clone()));
+            token::Client::new(&env, &token).transfer(
+                &env.current_contract_address(),
+                &treasury,
+                &amount,
+            );
+            env.events().publish(
+                (symbol_short!("fee"), symbol_short!("withdraw")),
+                (token, treasury, amount),
+            );
+        }
+        Ok(amount)
+    }
+
+    // ─── Keep storage alive ───
+    // Extends the contract instance (config and keys) and the listed games and
+    // open challenges. Games already extend themselves on every move; this
+    // covers games left idle, waiting challenges and a contract nobody has
+    // played for a while.
+    pub fn bump_ttl(
+        env: Env,
+        game_ids: Vec<u32>,
+        challenge_ids: Vec<u32>,
+    ) -> Result<(), BattleshipError> {
+        Self::require_admin(&env)?;
+        Self::extend_instance(&env);
+        for game_id in game_ids.iter() {
+            let state = Self::load_game(&env, game_id)?;
+            Self::extend_game(&env, game_id, &state);
+        }
+        for challenge_id in challenge_ids.iter() {
+            let challenge = Self::get_challenge(env.clone(), challenge_id)
+                .ok_or(BattleshipError::ChallengeNotFound)?;
+            Self::extend_challenge(&env, &challenge);
+        }
+        Ok(())
+    }
+
+    // ─── Register a salvo verification key ───
+    // `vk` checks salvo proofs answering exactly `shots` shots at once
+    // (`salvo_circuit/` compiled with SALVO = shots). Admin only.
+    pub fn set_salvo_key(env: Env, shots: u32, vk: VerificationKey) -> Result<(), BattleshipError> {
+        Self::require_admin(&env)?;
+        if shots == 0 || shots > MAX_SALVO {
+            return Err(BattleshipError::InvalidSalvo);
+        }
+        env.storage().instance().set(&DataKey::SalvoKey(shots), &vk);
+        Ok(())
     }
 
-    // ─── Start a new game ───
+    // ─── Invite a player to a new game ───
+    // Returns the game id every other call refers to. The game stays pending
+    // until player2 agrees with `accept_game`, within INVITE_TIMEOUT ledgers.
+    // With a wager, each player deposits the stake when committing their board;
+    // the contract holds the escrow until the game is won, drawn or cancelled.
     pub fn start_game(
         env: Env,
         player1: Address,
         player2: Address,
+        wager: Option<Wager>,
     ) -> Result<u32, BattleshipError> {
-        // Require auth from the caller
-        player1.require_auth();
+        let (game_id, state) = Self::open_game(&env, player1, player2, GameMode::Classic, wager)?;
+        Self::store_game(&env, game_id, &state);
+        Ok(game_id)
+    }
 
-        // If a previous game exists, clear its state so we can start fresh
-        if env
-            .storage()
-            .persistent()
-            .get::<_, bool>(&DataKey::GameStarted)
-            .unwrap_or(false)
-        {
-            // Clean up previous game state
-            let old_p1: Option<Address> = env.storage().persistent().get(&DataKey::Player1);
-            let old_p2: Option<Address> = env.storage().persistent().get(&DataKey::Player2);
-            if let Some(ref p) = old_p1 {
-                env.storage().persistent().remove(&DataKey::BoardHash(p.clone()));
-                env.storage().persistent().remove(&DataKey::HitsCount(p.clone()));
-            }
-            if let Some(ref p) = old_p2 {
-                env.storage().persistent().remove(&DataKey::BoardHash(p.clone()));
-                env.storage().persistent().remove(&DataKey::HitsCount(p.clone()));
+    // ─── Accept an invite ───
+    // Only the invited player2 can accept, and only before the invite expires
+    pub fn accept_game(env: Env, game_id: u32, player: Address) -> Result<(), BattleshipError> {
+        player.require_auth();
+
+        let mut state = Self::load_game(&env, game_id)?;
+        if player != state.player2.address {
+            return Err(BattleshipError::InvalidPlayer);
+        }
+        match state.status {
+            GameStatus::Invited => {}
+            GameStatus::Active => return Err(BattleshipError::GameAlreadyStarted),
+            GameStatus::Ended | GameStatus::Withdrawn => {
+                return Err(BattleshipError::GameAlreadyEnded)
             }
-            env.storage().persistent().remove(&DataKey::Winner);
-            env.storage().persistent().remove(&DataKey::GameEnded);
-            env.storage().persistent().remove(&DataKey::GameStarted);
-            env.storage().persistent().remove(&DataKey::TotalShots);
-            env.storage().persistent().remove(&DataKey::SessionId);
-            log!(&env, "Previous game state cleared for restart");
+        }
+        if env.ledger().sequence() > state.invite_deadline {
+            return Err(BattleshipError::InviteExpired);
         }
 
+        Self::activate(&env, game_id, &mut state);
+        Self::store_game(&env, game_id, &state);
+        Ok(())
+    }
 
-        // Generate session ID from ledger sequence
-        let session_id: u32 = env.ledger().sequence();
+    // ─── Start a new salvo game ───
+    // Players fire several shots per call through `fire_salvo` instead of `shoot`.
+    // Every salvo size the game can reach needs a registered key.
+    pub fn start_salvo(
+        env: Env,
+        player1: Address,
+        player2: Address,
+        size: SalvoSize,
+        wager: Option<Wager>,
+    ) -> Result<u32, BattleshipError> {
+        let (game_id, state) =
+            Self::open_game(&env, player1, player2, GameMode::Salvo(size), wager)?;
+        Self::store_game(&env, game_id, &state);
+        Ok(game_id)
+    }
 
-        // Store game state
-        env.storage()
-            .persistent()
-            .set(&DataKey::Player1, &player1);
-        env.storage()
-            .persistent()
-            .set(&DataKey::Player2, &player2);
-        env.storage()
-            .persistent()
-            .set(&DataKey::SessionId, &session_id);
-        env.storage().persistent().set(&DataKey::GameStarted, &true);
-        env.storage()
-            .persistent()
-            .set(&DataKey::GameEnded, &false);
-        env.storage()
-            .persistent()
-            .set(&DataKey::HitsCount(player1.clone()), &0u32);
+    // ─── Lobby: offer a game ───
+    // Anyone may accept an open challenge; naming `opponent` makes it a direct one.
+    // With a `rating_band`, the acceptor's Game Hub rating must be within that many
+    // points of the creator's. Returns the challenge id.
+    pub fn create_challenge(
+        env: Env,
+        creator: Address,
+        mode: GameMode,
+        wager: Option<Wager>,
+        opponent: Option<Address>,
+        rating_band: Option<u32>,
+    ) -> Result<u32, BattleshipError> {
+        creator.require_auth();
+
+        if opponent.as_ref() == Some(&creator) {
+            return Err(BattleshipError::InvalidPlayer);
+        }
+        if wager.as_ref().is_some_and(|wager| wager.stake <= 0) {
+            return Err(BattleshipError::InvalidWager);
+        }
+        if let GameMode::Salvo(size) = mode {
+            Self::check_salvo_keys(&env, size)?;
+        }
+        let by_creator = DataKey::OpenByCreator(creator.clone());
+        let waiting: u32 = env.storage().persistent().get(&by_creator).unwrap_or(0);
+        if waiting >= MAX_OPEN_PER_CREATOR {
+            return Err(BattleshipError::TooManyChallenges);
+        }
+
+        let id: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::ChallengeCounter)
+            .unwrap_or(0)
+            + 1;
         env.storage()
-            .persistent()
-            .set(&DataKey::HitsCount(player2.clone()), &0u32);
+            .instance()
+            .set(&DataKey::ChallengeCounter, &id);
+        let challenge = Challenge {
+            id,
+            creator: creator.clone(),
+            opponent,
+            mode,
+            token: wager.as_ref().map(|wager| wager.token.clone()),
+            stake: wager.map_or(0, |wager| wager.stake),
+            rating_band,
+            created_at: env.ledger().sequence(),
+            slot: Self::open_count(&env),
+        };
+        let storage = env.storage().persistent();
+        storage.set(&DataKey::Challenge(id), &challenge);
+        storage.set(&DataKey::OpenSlot(challenge.slot), &id);
+        storage.set(&by_creator, &(waiting + 1));
         env.storage()
-            .persistent()
-            .set(&DataKey::TotalShots, &0u32);
+            .instance()
+            .set(&DataKey::OpenCount, &(challenge.slot + 1));
+        Self::extend_challenge(&env, &challenge);
+        Self::extend_instance(&env);
+
+        env.events().publish(
+            (symbol_short!("lobby"), symbol_short!("create")),
+            (id, creator),
+        );
+        Ok(id)
+    }
 
-        // NOTE: Game Hub cross-contract call removed for reliability.
-        // In production, the game would register with the Hub here.
-        // The Hub address is: CB4VZAT2U3UC6XFK3N23SKRF2NDCMP3QHJYMCHHFMZO7MRQO6DQ2EMYG
-        log!(&env, "Game started without Hub registration (standalone mode)");
+    // ─── Lobby: take up a challenge ───
+    // Starts the game in the same transaction, so both the creator and the
+    // acceptor authorize it. The creator plays as player 1. Returns the game id.
+    pub fn accept_challenge(
+        env: Env,
+        challenge_id: u32,
+        player: Address,
+    ) -> Result<u32, BattleshipError> {
+        player.require_auth();
+
+        let challenge = Self::get_challenge(env.clone(), challenge_id)
+            .ok_or(BattleshipError::ChallengeNotFound)?;
+        if player == challenge.creator
+            || challenge.opponent.as_ref().is_some_and(|opponent| *opponent != player)
+        {
+            return Err(BattleshipError::InvalidPlayer);
+        }
+        if let Some(band) = challenge.rating_band {
+            let gap = Self::hub_rating(&env, &challenge.creator)? - Self::hub_rating(&env, &player)?;
+            if gap.abs() > band as i64 * RATING_SCALE {
+                return Err(BattleshipError::OutsideRatingBand);
+            }
+        }
+
+        Self::close_challenge(&env, &challenge);
+        let creator = challenge.creator;
+        let wager = challenge.token.map(|token| Wager {
+            token,
+            stake: challenge.stake,
+        });
+        // The acceptor has already agreed, so the invite is accepted on the spot
+        let (game_id, mut state) = Self::open_game(&env, creator, player, challenge.mode, wager)?;
+        Self::activate(&env, game_id, &mut state);
+        Self::store_game(&env, game_id, &state);
 
-        log!(&env, "Game started: session={}", session_id);
         env.events().publish(
-            (symbol_short!("game"), symbol_short!("start")),
-            session_id,
+            (symbol_short!("lobby"), symbol_short!("accept")),
+            (challenge_id, game_id),
         );
+        Ok(game_id)
+    }
+
+    // ─── Lobby: withdraw a challenge ───
+    pub fn cancel_challenge(env: Env, challenge_id: u32) -> Result<(), BattleshipError> {
+        let challenge = Self::get_challenge(env.clone(), challenge_id)
+            .ok_or(BattleshipError::ChallengeNotFound)?;
+        challenge.creator.require_auth();
 
-        Ok(session_id)
+        Self::close_challenge(&env, &challenge);
+        env.events().publish(
+            (symbol_short!("lobby"), symbol_short!("cancel")),
+            challenge_id,
+        );
+        Ok(())
     }
 
     // ─── Commit a board hash ───
+    // The placement proof shows the committed board holds every ship of the fleet,
+    // straight and non-overlapping, with each cell labelled by its ship id.
+    // Public signals: [board_hash, length of ships 1..=MAX_SHIPS (0 past the fleet)]
+    // In a wagered game the player's stake moves into escrow here.
     pub fn commit_board(
         env: Env,
+        game_id: u32,
         player: Address,
         board_hash: BytesN<32>,
+        placement_proof: Groth16Proof,
     ) -> Result<(), BattleshipError> {
         player.require_auth();
 
         // Verify game is active
-        if !env
-            .storage()
-            .persistent()
-            .get::<_, bool>(&DataKey::GameStarted)
-            .unwrap_or(false)
-        {
-            return Err(BattleshipError::GameNotStarted);
+        let mut state = Self::load_game(&env, game_id)?;
+        state.require_active()?;
+
+        // Verify player is in this game and has not committed yet
+        if state.player(&player)?.board.is_some() {
+            return Err(BattleshipError::BoardAlreadyCommitted);
         }
 
-        // Verify player is in this game
-        let p1: Address = env
-            .storage()
-            .persistent()
-            .get(&DataKey::Player1)
-            .unwrap();
-        let p2: Address = env
+        // ─── Verify the fleet placement proof ───
+        let placement_vk: VerificationKey = env
             .storage()
-            .persistent()
-            .get(&DataKey::Player2)
-            .unwrap();
-
-        if player != p1 && player != p2 {
-            return Err(BattleshipError::InvalidPlayer);
+            .instance()
+            .get(&DataKey::PlacementKey)
+            .ok_or(BattleshipError::NotInitialized)?;
+        let fleet = Self::fleet(&env)?;
+        let mut pub_signals = vec![&env, Fr::from_bytes(board_hash.clone())];
+        for ship in 0..MAX_SHIPS {
+            pub_signals.push_back(Self::field(&env, fleet.get(ship).unwrap_or(0)));
         }
 
-        // Check not already committed
-        if env
-            .storage()
-            .persistent()
-            .has(&DataKey::BoardHash(player.clone()))
-        {
-            return Err(BattleshipError::BoardAlreadyCommitted);
+        let valid = Self::verify_groth16(&env, &placement_vk, &placement_proof, &pub_signals)?;
+        if !valid {
+            return Err(BattleshipError::ProofVerificationFailed);
         }
 
         // Store the board hash
-        env.storage()
-            .persistent()
-            .set(&DataKey::BoardHash(player.clone()), &board_hash);
+        let wager = state.wager();
+        let (side, _) = state.sides_mut(&player)?;
+        side.board = Some(board_hash);
+
+        if let Some(wager) = wager {
+            token::Client::new(&env, &wager.token).transfer(
+                &player,
+                &env.current_contract_address(),
+                &wager.stake,
+            );
+            side.deposit = wager.stake;
+            env.events().publish(
+                (symbol_short!("wager"), symbol_short!("deposit")),
+                (game_id, player.clone(), wager.stake),
+            );
+        }
+        // Player 1 owes the first shot once both boards are in
+        if state.player1.board.is_some() && state.player2.board.is_some() {
+            let player1 = state.player1.address.clone();
+            Self::start_clock(&env, &mut state, &player1);
+        }
+        Self::store_game(&env, game_id, &state);
 
         log!(&env, "Board committed by player");
         env.events().publish(
             (symbol_short!("board"), symbol_short!("commit")),
-            player,
+            (game_id, player),
         );
 
         Ok(())
     }
 
     // ─── Submit a shot with ZK proof ───
+    // The proof shows the target's committed board answers `shot_index` with
+    // `claimed_result` and that `sunk_ship` is the id of the ship the hit
+    // completed, or 0 if it completed none.
+    // Public signals: [board_hash, shot_index, claimed_result, sunk_ship, prior_hits],
+    // where `prior_hits` is the bitmask of cells already hit on the target's board.
+    // Sinking the target's last ship wins the game.
     pub fn shoot(
         env: Env,
+        game_id: u32,
         shooter: Address,
         shot_index: u32,
         claimed_result: u32, // 0 = miss, 1 = hit
+        sunk_ship: u32,
         proof: Groth16Proof,
-        pub_signals: Vec<Fr>,
     ) -> Result<u32, BattleshipError> {
         shooter.require_auth();
 
         // Verify game state
-        if !env
-            .storage()
-            .persistent()
-            .get::<_, bool>(&DataKey::GameStarted)
-            .unwrap_or(false)
-        {
-            return Err(BattleshipError::GameNotStarted);
+        let mut state = Self::load_game(&env, game_id)?;
+        state.require_active()?;
+        if state.mode != GameMode::Classic {
+            return Err(BattleshipError::WrongGameMode);
         }
 
-        if env
-            .storage()
-            .persistent()
-            .get::<_, bool>(&DataKey::GameEnded)
-            .unwrap_or(false)
-        {
-            return Err(BattleshipError::GameAlreadyEnded);
-        }
+        let shot = Shot {
+            index: shot_index,
+            result: claimed_result,
+            sunk: sunk_ship,
+        };
+        Self::settle_shot(&env, game_id, &mut state, &shooter, shot, &proof)?;
+        Self::store_game(&env, game_id, &state);
 
-        // Validate shot index
-        if shot_index >= 25 {
-            return Err(BattleshipError::InvalidShotIndex);
-        }
+        Ok(state.player(&shooter)?.hits)
+    }
 
-        // Verify the player is in this game
-        let p1: Address = env
-            .storage()
-            .persistent()
-            .get(&DataKey::Player1)
-            .unwrap();
-        let p2: Address = env
-            .storage()
-            .persistent()
-            .get(&DataKey::Player2)
-            .unwrap();
+    // ─── Settle several turns at once ───
+    // Each shot keeps its own single-shot proof, but all of them are checked
+    // together with one randomized batch pairing check. Turns still alternate:
+    // `shooter` fires the first shot and every other one after it, the opponent
+    // the shots in between, so a batch of more than one shot needs both players
+    // to sign. Shots resolve in order, so each proof's `prior_hits` counts the
+    // hits settled on that board before it in the batch.
+    pub fn settle_shots(
+        env: Env,
+        game_id: u32,
+        shooter: Address,
+        shots: Vec<Shot>,
+        proofs: Vec<Groth16Proof>,
+    ) -> Result<u32, BattleshipError> {
+        shooter.require_auth();
 
-        if shooter != p1 && shooter != p2 {
-            return Err(BattleshipError::InvalidPlayer);
+        let mut state = Self::load_game(&env, game_id)?;
+        state.require_active()?;
+        if state.mode != GameMode::Classic {
+            return Err(BattleshipError::WrongGameMode);
+        }
+        if shots.is_empty() || shots.len() != proofs.len() {
+            return Err(BattleshipError::MalformedProof);
+        }
+        if shots.len() > MAX_BATCH {
+            return Err(BattleshipError::BatchTooLarge);
+        }
+        Self::require_shot_turn(&state, &shooter)?;
+        let opponent = state.opponent(&shooter)?.address.clone();
+        if shots.len() > 1 {
+            opponent.require_auth();
         }
 
-        // Ensure both boards are committed
-        if !env
-            .storage()
-            .persistent()
-            .has(&DataKey::BoardHash(p1.clone()))
-            || !env
-                .storage()
-                .persistent()
-                .has(&DataKey::BoardHash(p2.clone()))
-        {
-            return Err(BattleshipError::NotAllBoardsCommitted);
+        // Split the batch by turn: even shots are `shooter`'s, odd ones the opponent's
+        let mut turns = [(shooter.clone(), vec![&env]), (opponent, vec![&env])];
+        for (i, shot) in shots.iter().enumerate() {
+            turns[i % 2].1.push_back(shot);
+        }
+        let mut boards = [None, None];
+        for ((player, fired), board) in turns.iter().zip(boards.iter_mut()) {
+            if !fired.is_empty() {
+                let (target, board_hash, recorded) =
+                    Self::prepare_shots(&env, game_id, &state, player, fired)?;
+                let hits = Self::hit_mask(&recorded);
+                *board = Some((target, board_hash, recorded, hits));
+            }
         }
 
-        // ─── Verify Groth16 ZK Proof ───
+        // ─── Verify the batch of Groth16 proofs ───
         let vk: VerificationKey = env
             .storage()
-            .persistent()
+            .instance()
             .get(&DataKey::VerificationKey)
-            .unwrap();
+            .ok_or(BattleshipError::NotInitialized)?;
+        let mut pub_signals = vec![&env];
+        for (i, shot) in shots.iter().enumerate() {
+            if let Some((_, board_hash, _, hits)) = &mut boards[i % 2] {
+                pub_signals.push_back(vec![
+                    &env,
+                    Fr::from_bytes(board_hash.clone()),
+                    Self::field(&env, shot.index),
+                    Self::field(&env, shot.result),
+                    Self::field(&env, shot.sunk),
+                    Self::field(&env, *hits),
+                ]);
+                if shot.result == 1 {
+                    *hits |= 1 << shot.index;
+                }
+            }
+        }
 
-        let valid = Self::verify_groth16(&env, &vk, &proof, &pub_signals)?;
+        let valid = Self::verify_batch(&env, &vk, &proofs, &pub_signals)?;
         if !valid {
             return Err(BattleshipError::ProofVerificationFailed);
         }
 
-        // Update hit count if it's a hit
-        if claimed_result == 1 {
-            let current_hits: u32 = env
-                .storage()
-                .persistent()
-                .get(&DataKey::HitsCount(shooter.clone()))
-                .unwrap_or(0);
+        for (i, shot) in shots.iter().enumerate() {
+            if let Some((_, _, recorded, _)) = &mut boards[i % 2] {
+                Self::record_shot(&env, game_id, &mut state, &turns[i % 2].0, recorded, shot)?;
+            }
+        }
+        for (target, _, recorded, _) in boards.iter().flatten() {
             env.storage()
                 .persistent()
-                .set(&DataKey::HitsCount(shooter.clone()), &(current_hits + 1));
+                .set(&DataKey::ShotsAt(game_id, target.clone()), recorded);
         }
+        Self::store_game(&env, game_id, &state);
 
-        // Update total shots
-        let total: u32 = env
-            .storage()
-            .persistent()
-            .get(&DataKey::TotalShots)
-            .unwrap_or(0);
-        env.storage()
-            .persistent()
-            .set(&DataKey::TotalShots, &(total + 1));
+        Ok(state.player(&shooter)?.hits)
+    }
 
-        // Emit shot event
-        env.events().publish(
-            (symbol_short!("shot"), symbol_short!("fire")),
-            (shooter.clone(), shot_index, claimed_result),
-        );
+    // ─── Fire a salvo with one batched ZK proof ───
+    // The defender answers every shot of the salvo with a single proof. Shots
+    // resolve in order, so a later shot sees the hits of the earlier ones.
+    // Public signals: [board_hash, shot_indices..., claimed_results..., sunk_ships..., prior_hits]
+    pub fn fire_salvo(
+        env: Env,
+        game_id: u32,
+        shooter: Address,
+        shot_indices: Vec<u32>,
+        claimed_results: Vec<u32>,
+        sunk_ships: Vec<u32>,
+        proof: Groth16Proof,
+    ) -> Result<u32, BattleshipError> {
+        shooter.require_auth();
 
-        log!(
-            &env,
-            "Shot fired: index={}, result={}",
-            shot_index,
-            claimed_result
-        );
+        let mut state = Self::load_game(&env, game_id)?;
+        state.require_active()?;
+        let size = Self::salvo_size(&env, &state, &shooter)?;
+        if shot_indices.len() != size || claimed_results.len() != size || sunk_ships.len() != size {
+            return Err(BattleshipError::InvalidSalvo);
+        }
 
-        let hits: u32 = env
-            .storage()
-            .persistent()
-            .get(&DataKey::HitsCount(shooter.clone()))
-            .unwrap_or(0);
-        Ok(hits)
+        let salvo = Self::zip_shots(&env, &shot_indices, &claimed_results, &sunk_ships);
+        Self::settle_salvo(&env, game_id, &mut state, &shooter, &salvo, &proof)?;
+        Self::store_game(&env, game_id, &state);
+
+        Ok(state.player(&shooter)?.hits)
     }
 
-    // ─── End the game ───
-    pub fn end_game(env: Env, caller: Address) -> Result<Address, BattleshipError> {
-        caller.require_auth();
+    // ─── Call shots and wait for the defender's proof ───
+    // Shot proofs come from the defender, who could stall by withholding one.
+    // Calling the cells on-chain puts the defender on the clock until the
+    // shots are settled, by the defender with `answer_shots` or by the shooter
+    // as usual. Classic games call one cell and salvo games a full salvo.
+    pub fn call_shots(
+        env: Env,
+        game_id: u32,
+        shooter: Address,
+        shot_indices: Vec<u32>,
+    ) -> Result<(), BattleshipError> {
+        shooter.require_auth();
 
-        if !env
-            .storage()
-            .persistent()
-            .get::<_, bool>(&DataKey::GameStarted)
-            .unwrap_or(false)
-        {
-            return Err(BattleshipError::GameNotStarted);
+        let mut state = Self::load_game(&env, game_id)?;
+        state.require_active()?;
+        match &state.on_clock {
+            None => return Err(BattleshipError::NotAllBoardsCommitted),
+            Some(owing) if *owing != shooter || !state.called.is_empty() => {
+                return Err(BattleshipError::NotYourTurn)
+            }
+            Some(_) => {}
+        }
+        let size = match state.mode {
+            GameMode::Classic => 1,
+            GameMode::Salvo(_) => Self::salvo_size(&env, &state, &shooter)?,
+        };
+        if shot_indices.len() != size {
+            return Err(BattleshipError::InvalidSalvo);
         }
 
-        if env
-            .storage()
-            .persistent()
-            .get::<_, bool>(&DataKey::GameEnded)
-            .unwrap_or(false)
-        {
-            return Err(BattleshipError::GameAlreadyEnded);
+        // Results are not known yet; this only checks the cells
+        let mut called = vec![&env];
+        for index in shot_indices.iter() {
+            called.push_back(Shot { index, result: 0, sunk: 0 });
+        }
+        Self::prepare_shots(&env, game_id, &state, &shooter, &called)?;
+
+        let defender = state.opponent(&shooter)?.address.clone();
+        Self::start_clock(&env, &mut state, &defender);
+        state.called = shot_indices.clone();
+        Self::store_game(&env, game_id, &state);
+
+        env.events().publish(
+            (symbol_short!("shot"), symbol_short!("call")),
+            (game_id, shooter, shot_indices),
+        );
+        Ok(())
+    }
+
+    // ─── Answer called shots ───
+    // The defender settles the shots called at its board with the same proof
+    // the shooter would submit. Returns the shooter's hits.
+    pub fn answer_shots(
+        env: Env,
+        game_id: u32,
+        defender: Address,
+        claimed_results: Vec<u32>,
+        sunk_ships: Vec<u32>,
+        proof: Groth16Proof,
+    ) -> Result<u32, BattleshipError> {
+        defender.require_auth();
+
+        let mut state = Self::load_game(&env, game_id)?;
+        state.require_active()?;
+        let shooter = state.opponent(&defender)?.address.clone();
+        if state.called.is_empty() || state.on_clock.as_ref() != Some(&defender) {
+            return Err(BattleshipError::NoCalledShot);
+        }
+        let size = state.called.len();
+        if claimed_results.len() != size || sunk_ships.len() != size {
+            return Err(BattleshipError::InvalidSalvo);
+        }
+
+        let shots = Self::zip_shots(&env, &state.called, &claimed_results, &sunk_ships);
+        match state.mode {
+            GameMode::Classic => {
+                let shot = shots.get_unchecked(0);
+                Self::settle_shot(&env, game_id, &mut state, &shooter, shot, &proof)?;
+            }
+            GameMode::Salvo(_) => {
+                Self::settle_salvo(&env, game_id, &mut state, &shooter, &shots, &proof)?;
+            }
+        }
+        Self::store_game(&env, game_id, &state);
+
+        Ok(state.player(&shooter)?.hits)
+    }
+
+    // ─── End the game early ───
+    // A game normally ends when a fleet is sunk. Stopping before that gives the
+    // win to whoever sank more ships, then to whoever landed more hits.
+    // Equal scores are a draw, returned as `None`, and split the escrow.
+    // A result by score moves the pot and both ratings, so the caller must be
+    // a player and their opponent must authorize it too.
+    pub fn end_game(
+        env: Env,
+        game_id: u32,
+        caller: Address,
+    ) -> Result<Option<Address>, BattleshipError> {
+        caller.require_auth();
+
+        let mut state = Self::load_game(&env, game_id)?;
+        state.require_active()?;
+        state.opponent(&caller)?.address.require_auth();
+
+        let p1_score = Self::score(&state.player1, &state.player2);
+        let p2_score = Self::score(&state.player2, &state.player1);
+
+        let winner = match p1_score.cmp(&p2_score) {
+            Ordering::Greater => Some(state.player1.address.clone()),
+            Ordering::Less => Some(state.player2.address.clone()),
+            Ordering::Equal => None,
+        };
+
+        Self::finish(&env, game_id, &mut state, winner.clone());
+        Self::store_game(&env, game_id, &state);
+
+        Ok(winner)
+    }
+
+    // ─── Forfeit the game ───
+    // The opponent wins and takes the escrow
+    pub fn forfeit(env: Env, game_id: u32, player: Address) -> Result<(), BattleshipError> {
+        player.require_auth();
+
+        let mut state = Self::load_game(&env, game_id)?;
+        state.require_active()?;
+        let opponent = state.opponent(&player)?.address.clone();
+
+        Self::finish(&env, game_id, &mut state, Some(opponent));
+        Self::store_game(&env, game_id, &state);
+        Ok(())
+    }
+
+    // ─── Claim the win from a stalled opponent ───
+    // The clock starts once both boards are committed and runs against whoever
+    // owes the next action: the shooter owes a shot, and after `call_shots` the
+    // defender owes its proof. The other player claims once MOVE_TIMEOUT
+    // ledgers pass.
+    pub fn claim_timeout(env: Env, game_id: u32, player: Address) -> Result<(), BattleshipError> {
+        player.require_auth();
+
+        let mut state = Self::load_game(&env, game_id)?;
+        state.require_active()?;
+        let opponent = state.opponent(&player)?.address.clone();
+
+        match &state.on_clock {
+            None => return Err(BattleshipError::NotAllBoardsCommitted),
+            Some(owing) if *owing != opponent => return Err(BattleshipError::AwaitingYourMove),
+            Some(_) => {}
+        }
+        if env.ledger().sequence() <= state.clock_start + MOVE_TIMEOUT {
+            return Err(BattleshipError::TimeoutNotReached);
+        }
+
+        Self::finish(&env, game_id, &mut state, Some(player));
+        Self::store_game(&env, game_id, &state);
+        Ok(())
+    }
+
+    // ─── Cancel the game by mutual consent ───
+    // Each player asks once; the second request ends the game without a winner
+    // and refunds every deposit. Returns whether the game was cancelled.
+    // An unanswered request lapses after REQUEST_TTL ledgers.
+    // A pending invite is withdrawn or declined by either player alone, and so
+    // is a game still waiting for a board.
+    pub fn cancel(env: Env, game_id: u32, player: Address) -> Result<bool, BattleshipError> {
+        player.require_auth();
+
+        let mut state = Self::load_game(&env, game_id)?;
+        let opponent = state.opponent(&player)?.address.clone();
+        if state.status == GameStatus::Invited {
+            state.status = GameStatus::Withdrawn;
+            Self::store_game(&env, game_id, &state);
+            env.events().publish(
+                (symbol_short!("game"), symbol_short!("cancel")),
+                game_id,
+            );
+            return Ok(true);
+        }
+        state.require_active()?;
+
+        // Nobody is on the clock until both boards are committed, so until
+        // then either player can call the game off alone
+        if state.on_clock.is_some() {
+            let request = DataKey::CancelRequest(game_id, player.clone());
+            env.storage().temporary().set(&request, &true);
+            env.storage()
+                .temporary()
+                .extend_ttl(&request, REQUEST_TTL, REQUEST_TTL);
+            let agreed = env
+                .storage()
+                .temporary()
+                .get::<_, bool>(&DataKey::CancelRequest(game_id, opponent))
+                .unwrap_or(false);
+            if !agreed {
+                env.events().publish(
+                    (symbol_short!("game"), symbol_short!("cancel_rq")),
+                    (game_id, player),
+                );
+                return Ok(false);
+            }
+        }
+
+        Self::close(&env, game_id, &mut state, None, false);
+        Self::store_game(&env, game_id, &state);
+        env.events().publish(
+            (symbol_short!("game"), symbol_short!("cancel")),
+            game_id,
+        );
+        Ok(true)
+    }
+
+    // ─── Reveal a board after the game for audit ───
+    // Checks the revealed fleet is legal and that every result and sunk ship
+    // claimed against this board during the game matches it. A failed audit flags the player as
+    // a cheater and hands the win to the opponent (or to nobody if both cheated).
+    // The escrow was already paid out when the game ended and is not moved again.
+    // Returns whether the board passed.
+    pub fn reveal_board(
+        env: Env,
+        game_id: u32,
+        player: Address,
+        board: Vec<u32>,
+        salt: BytesN<32>,
+    ) -> Result<bool, BattleshipError> {
+        player.require_auth();
+
+        let mut state = Self::load_game(&env, game_id)?;
+        state.player(&player)?;
+        if !state.is_over() {
+            return Err(BattleshipError::GameNotEnded);
+        }
+        let revealed_key = DataKey::RevealedBoard(game_id, player.clone());
+        if env.storage().persistent().has(&revealed_key) {
+            return Err(BattleshipError::BoardAlreadyRevealed);
+        }
+        let board_hash = state
+            .player(&player)?
+            .board
+            .clone()
+            .ok_or(BattleshipError::NotAllBoardsCommitted)?;
+        if board.len() != BOARD_CELLS {
+            return Err(BattleshipError::InvalidBoard);
         }
 
-        let p1: Address = env
+        // The reveal must open the commitment before it is audited
+        if Self::board_commitment(&board, &salt) != Some(board_hash.to_array()) {
+            return Err(BattleshipError::CommitmentMismatch);
+        }
+
+        let fleet = Self::fleet(&env)?;
+        let shots: Vec<Shot> = env
             .storage()
             .persistent()
-            .get(&DataKey::Player1)
-            .unwrap();
-        let p2: Address = env
-            .storage()
+            .get(&DataKey::ShotsAt(game_id, player.clone()))
+            .unwrap_or(vec![&env]);
+        let honest = Self::is_legal_fleet(&board, &fleet) && Self::shots_match(&board, &shots);
+
+        env.storage().persistent().set(&revealed_key, &board);
+        env.events().publish(
+            (symbol_short!("board"), symbol_short!("reveal")),
+            (game_id, player.clone(), honest),
+        );
+
+        if !honest {
+            let (side, opponent) = state.sides_mut(&player)?;
+            side.cheater = true;
+            let winner = if opponent.cheater {
+                None
+            } else {
+                Some(opponent.address.clone())
+            };
+            state.winner = winner.clone();
+
+            log!(&env, "Board audit failed: outcome reversed");
+            env.events().publish(
+                (symbol_short!("game"), symbol_short!("cheat")),
+                (game_id, player, winner),
+            );
+        }
+        Self::store_game(&env, game_id, &state);
+
+        Ok(honest)
+    }
+
+    // ─── View functions ───
+
+    pub fn get_game(env: Env, game_id: u32) -> Option<GameState> {
+        env.storage().persistent().get(&DataKey::Game(game_id))
+    }
+
+    pub fn get_hits(env: Env, game_id: u32, player: Address) -> u32 {
+        Self::get_game(env, game_id)
+            .map_or(0, |state| state.player(&player).map_or(0, |side| side.hits))
+    }
+
+    pub fn get_game_state(env: Env, game_id: u32) -> (bool, bool) {
+        match Self::get_game(env, game_id).map(|state| state.status) {
+            None | Some(GameStatus::Invited) => (false, false),
+            Some(GameStatus::Active) => (true, false),
+            Some(GameStatus::Ended) => (true, true),
+            Some(GameStatus::Withdrawn) => (false, true),
+        }
+    }
+
+    // Last ledger player2 can accept on, while the game is still an invite
+    pub fn get_invite_deadline(env: Env, game_id: u32) -> Option<u32> {
+        Self::get_game(env, game_id)
+            .filter(|state| state.status == GameStatus::Invited)
+            .map(|state| state.invite_deadline)
+    }
+
+    pub fn get_winner(env: Env, game_id: u32) -> Option<Address> {
+        Self::get_game(env, game_id).and_then(|state| state.winner)
+    }
+
+    pub fn get_fleet(env: Env) -> Vec<u32> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Fleet)
+            .unwrap_or(vec![&env])
+    }
+
+    pub fn get_ship_cells(env: Env) -> u32 {
+        Self::get_fleet(env).iter().sum()
+    }
+
+    pub fn get_board_hash(env: Env, game_id: u32, player: Address) -> Option<BytesN<32>> {
+        Self::get_game(env, game_id).and_then(|state| state.player(&player).ok()?.board.clone())
+    }
+
+    pub fn get_shots_at(env: Env, game_id: u32, player: Address) -> Vec<Shot> {
+        env.storage()
             .persistent()
-            .get(&DataKey::Player2)
-            .unwrap();
+            .get(&DataKey::ShotsAt(game_id, player))
+            .unwrap_or(vec![&env])
+    }
 
-        // Determine winner by hit count
-        let p1_hits: u32 = env
-            .storage()
+    pub fn get_revealed_board(env: Env, game_id: u32, player: Address) -> Option<Vec<u32>> {
+        env.storage()
             .persistent()
-            .get(&DataKey::HitsCount(p1.clone()))
-            .unwrap_or(0);
-        let p2_hits: u32 = env
-            .storage()
+            .get(&DataKey::RevealedBoard(game_id, player))
+    }
+
+    // Shots `shooter` must fire in their next salvo, or `WrongGameMode` outside salvo games
+    pub fn get_salvo_size(
+        env: Env,
+        game_id: u32,
+        shooter: Address,
+    ) -> Result<u32, BattleshipError> {
+        let state = Self::load_game(&env, game_id)?;
+        Self::salvo_size(&env, &state, &shooter)
+    }
+
+    // Ids of `player`'s ships sunk so far, in the order they went down
+    pub fn get_sunk_ships(env: Env, game_id: u32, player: Address) -> Vec<u32> {
+        Self::get_game(env.clone(), game_id)
+            .and_then(|state| Some(state.player(&player).ok()?.sunk.clone()))
+            .unwrap_or(vec![&env])
+    }
+
+    pub fn get_fee_bps(env: Env) -> u32 {
+        env.storage().instance().get(&DataKey::FeeBps).unwrap_or(0)
+    }
+
+    pub fn get_treasury(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::Treasury)
+    }
+
+    // Fees collected in `token` and not yet withdrawn
+    pub fn get_fees(env: Env, token: Address) -> i128 {
+        env.storage()
             .persistent()
-            .get(&DataKey::HitsCount(p2.clone()))
-            .unwrap_or(0);
+            .get(&DataKey::Fees(token))
+            .unwrap_or(0)
+    }
 
-        let player1_won = p1_hits >= p2_hits;
-        let winner = if player1_won { p1.clone() } else { p2.clone() };
+    pub fn get_wager(env: Env, game_id: u32) -> Option<Wager> {
+        Self::get_game(env, game_id).and_then(|state| state.wager())
+    }
+
+    // Stakes deposited and not yet paid out
+    pub fn get_escrow(env: Env, game_id: u32) -> i128 {
+        Self::get_game(env, game_id)
+            .map_or(0, |state| state.player1.deposit + state.player2.deposit)
+    }
 
-        // Store winner and mark game ended
+    pub fn get_challenge(env: Env, challenge_id: u32) -> Option<Challenge> {
         env.storage()
             .persistent()
-            .set(&DataKey::Winner, &winner);
-        env.storage().persistent().set(&DataKey::GameEnded, &true);
+            .get(&DataKey::Challenge(challenge_id))
+    }
+
+    // Challenges still waiting for an opponent, `limit` (at most MAX_PAGE) of
+    // them starting at slot `start`. Slots are not in creation order: a closed
+    // challenge's slot goes to the last open one.
+    pub fn list_open_challenges(env: Env, start: u32, limit: u32) -> Vec<Challenge> {
+        let end = Self::open_count(&env).min(start.saturating_add(limit.min(MAX_PAGE)));
+        let mut page = vec![&env];
+        for slot in start..end {
+            let id: Option<u32> = env.storage().persistent().get(&DataKey::OpenSlot(slot));
+            if let Some(challenge) = id.and_then(|id| Self::get_challenge(env.clone(), id)) {
+                page.push_back(challenge);
+            }
+        }
+        page
+    }
+
+    pub fn is_cheater(env: Env, game_id: u32, player: Address) -> bool {
+        Self::get_game(env, game_id)
+            .is_some_and(|state| state.player(&player).is_ok_and(|side| side.cheater))
+    }
+
+    // ─── Internal: game state helpers ───
+
+    fn load_game(env: &Env, game_id: u32) -> Result<GameState, BattleshipError> {
+        env.storage()
+            .persistent()
+            .get(&DataKey::Game(game_id))
+            .ok_or(BattleshipError::GameNotStarted)
+    }
+
+    // Writes the game back and extends its entries; each call does this once
+    fn store_game(env: &Env, game_id: u32, state: &GameState) {
+        env.storage()
+            .persistent()
+            .set(&DataKey::Game(game_id), state);
+        Self::extend_game(env, game_id, state);
+    }
+
+    // Sets up a new game as a pending invite, for the caller to store
+    fn open_game(
+        env: &Env,
+        player1: Address,
+        player2: Address,
+        mode: GameMode,
+        wager: Option<Wager>,
+    ) -> Result<(u32, GameState), BattleshipError> {
+        // Require auth from the caller
+        player1.require_auth();
+
+        if player1 == player2 {
+            return Err(BattleshipError::InvalidPlayer);
+        }
+        if wager.as_ref().is_some_and(|wager| wager.stake <= 0) {
+            return Err(BattleshipError::InvalidWager);
+        }
+        if let GameMode::Salvo(size) = mode {
+            Self::check_salvo_keys(env, size)?;
+        }
+
+        let game_id: u32 = env
+            .storage()
+            .instance()
+            .get(&DataKey::GameCounter)
+            .unwrap_or(0)
+            + 1;
+        env.storage()
+            .instance()
+            .set(&DataKey::GameCounter, &game_id);
 
-        // NOTE: Game Hub end_game cross-contract call removed for reliability.
-        // In production, the result would be registered with the Hub here.
-        log!(&env, "Game ended in standalone mode (no Hub call)");
+        let side = |address: &Address| PlayerState {
+            address: address.clone(),
+            board: None,
+            hits: 0,
+            sunk: vec![env],
+            deposit: 0,
+            cheater: false,
+        };
+        let deadline = env.ledger().sequence() + INVITE_TIMEOUT;
+        let state = GameState {
+            player1: side(&player1),
+            player2: side(&player2),
+            mode,
+            status: GameStatus::Invited,
+            total_shots: 0,
+            on_clock: None,
+            clock_start: 0,
+            called: vec![env],
+            winner: None,
+            invite_deadline: deadline,
+            token: wager.as_ref().map(|wager| wager.token.clone()),
+            stake: wager.map_or(0, |wager| wager.stake),
+            fee_bps: Self::get_fee_bps(env.clone()),
+            hub_session: None,
+        };
 
+        log!(env, "Game invite sent: game={}", game_id);
         env.events().publish(
-            (symbol_short!("game"), symbol_short!("end")),
-            winner.clone(),
+            (symbol_short!("game"), symbol_short!("invite")),
+            (game_id, player2, deadline),
         );
+        Ok((game_id, state))
+    }
 
-        log!(&env, "Game ended! Winner determined.");
+    // Starts play on an accepted invite and registers the session with the Game Hub
+    fn activate(env: &Env, game_id: u32, state: &mut GameState) {
+        state.status = GameStatus::Active;
 
-        Ok(winner)
+        // Each player's points are their stake. Hub session ids are shared by
+        // every game, so draw one rather than reuse game_id, and draw again
+        // if another game already holds it.
+        if let Some(hub) = Self::get_game_hub(env.clone()) {
+            let hub = GameHubClient::new(env, &hub);
+            for attempt in 1..=SESSION_ATTEMPTS {
+                let session_id = env.prng().gen_range::<u64>(0..=u32::MAX as u64) as u32;
+                let result = hub.try_start_game(
+                    &env.current_contract_address(),
+                    &session_id,
+                    &state.player1.address,
+                    &state.player2.address,
+                    &state.stake,
+                    &state.stake,
+                );
+                match result {
+                    Ok(_) => {
+                        state.hub_session = Some(session_id);
+                        break;
+                    }
+                    Err(Ok(HubError::SessionExists)) if attempt < SESSION_ATTEMPTS => {}
+                    Err(_) => {
+                        Self::hub_failed(env, game_id, symbol_short!("start"));
+                        break;
+                    }
+                }
+            }
+        }
+
+        log!(env, "Game started: game={}", game_id);
+        env.events().publish(
+            (symbol_short!("game"), symbol_short!("start")),
+            game_id,
+        );
     }
 
-    // ─── View functions ───
+    // Every salvo size the game can reach needs a registered key
+    fn check_salvo_keys(env: &Env, size: SalvoSize) -> Result<(), BattleshipError> {
+        let sizes = match size {
+            SalvoSize::Fixed(shots) => shots..=shots,
+            SalvoSize::SurvivingShips => 1..=Self::fleet(env)?.len(),
+        };
+        for shots in sizes {
+            if shots == 0 || !env.storage().instance().has(&DataKey::SalvoKey(shots)) {
+                return Err(BattleshipError::InvalidSalvo);
+            }
+        }
+        Ok(())
+    }
 
-    pub fn get_hits(env: Env, player: Address) -> u32 {
+    fn open_count(env: &Env) -> u32 {
         env.storage()
-            .persistent()
-            .get(&DataKey::HitsCount(player))
+            .instance()
+            .get(&DataKey::OpenCount)
             .unwrap_or(0)
     }
 
-    pub fn get_game_state(env: Env) -> (bool, bool) {
-        let started = env
+    // Removes the challenge and fills its slot with the last open one, so
+    // closing touches a fixed number of entries however long the list is
+    fn close_challenge(env: &Env, challenge: &Challenge) {
+        let storage = env.storage().persistent();
+        storage.remove(&DataKey::Challenge(challenge.id));
+
+        let last = Self::open_count(env) - 1;
+        if challenge.slot != last {
+            let moved_id: u32 = storage.get(&DataKey::OpenSlot(last)).unwrap();
+            let mut moved: Challenge = storage.get(&DataKey::Challenge(moved_id)).unwrap();
+            moved.slot = challenge.slot;
+            storage.set(&DataKey::Challenge(moved_id), &moved);
+            storage.set(&DataKey::OpenSlot(challenge.slot), &moved_id);
+        }
+        storage.remove(&DataKey::OpenSlot(last));
+        env.storage().instance().set(&DataKey::OpenCount, &last);
+
+        let by_creator = DataKey::OpenByCreator(challenge.creator.clone());
+        let waiting: u32 = storage.get(&by_creator).unwrap_or(1);
+        if waiting > 1 {
+            storage.set(&by_creator, &(waiting - 1));
+        } else {
+            storage.remove(&by_creator);
+        }
+    }
+
+    fn extend_challenge(env: &Env, challenge: &Challenge) {
+        Self::extend(env, &DataKey::Challenge(challenge.id));
+        Self::extend(env, &DataKey::OpenSlot(challenge.slot));
+        Self::extend(env, &DataKey::OpenByCreator(challenge.creator.clone()));
+    }
+
+    // `player`'s Game Hub rating for this contract's game type
+    fn hub_rating(env: &Env, player: &Address) -> Result<i64, BattleshipError> {
+        let hub = Self::get_game_hub(env.clone()).ok_or(BattleshipError::RatingUnavailable)?;
+        let hub = GameHubClient::new(env, &hub);
+        let Ok(Ok(Some(game_type))) = hub.try_get_game_type(&env.current_contract_address())
+        else {
+            return Err(BattleshipError::RatingUnavailable);
+        };
+        match hub.try_get_rating(player, &game_type) {
+            Ok(Ok(rating)) => Ok(rating.rating),
+            _ => Err(BattleshipError::RatingUnavailable),
+        }
+    }
+
+    fn require_admin(env: &Env) -> Result<(), BattleshipError> {
+        let admin: Address = env
+            .storage()
+            .instance()
+            .get(&DataKey::Admin)
+            .ok_or(BattleshipError::NotInitialized)?;
+        admin.require_auth();
+        Ok(())
+    }
+
+    // Checks a batch of shot claims before its proof is verified: both boards are
+    // committed, indices are on the grid and fresh, and each claim is well formed.
+    // Returns the target, the target's board hash and the shots already at it.
+    fn prepare_shots(
+        env: &Env,
+        game_id: u32,
+        state: &GameState,
+        shooter: &Address,
+        new_shots: &Vec<Shot>,
+    ) -> Result<(Address, BytesN<32>, Vec<Shot>), BattleshipError> {
+        // Verify the player is in this game
+        let target = state.opponent(shooter)?;
+
+        // Ensure both boards are committed
+        if state.player(shooter)?.board.is_none() {
+            return Err(BattleshipError::NotAllBoardsCommitted);
+        }
+        let board_hash = target
+            .board
+            .clone()
+            .ok_or(BattleshipError::NotAllBoardsCommitted)?;
+        let target = target.address.clone();
+
+        let fleet_size = Self::fleet(env)?.len();
+        let shots: Vec<Shot> = env
+            .storage()
+            .persistent()
+            .get(&DataKey::ShotsAt(game_id, target.clone()))
+            .unwrap_or(vec![env]);
+        let mut fired = shots.iter().fold(0u32, |mask, shot| mask | 1 << shot.index);
+        for shot in new_shots.iter() {
+            // Validate shot index
+            if shot.index >= BOARD_CELLS {
+                return Err(BattleshipError::InvalidShotIndex);
+            }
+            if shot.result > 1 || (shot.result == 0 && shot.sunk != 0) || shot.sunk > fleet_size {
+                return Err(BattleshipError::InvalidShotResult);
+            }
+            // Every cell is fired at once
+            if fired & (1 << shot.index) != 0 {
+                return Err(BattleshipError::CellAlreadyShot);
+            }
+            fired |= 1 << shot.index;
+        }
+        Ok((target, board_hash, shots))
+    }
+
+    // Shots alternate: the player on the clock owes the next one. Once cells are
+    // called the defender is on the clock, and the called shots are settled on
+    // the shooter's behalf, so the defender cannot fire back until they are.
+    fn require_shot_turn(state: &GameState, shooter: &Address) -> Result<(), BattleshipError> {
+        state.opponent(shooter)?;
+        match &state.on_clock {
+            None => Err(BattleshipError::NotAllBoardsCommitted),
+            Some(owing) if (owing == shooter) != state.called.is_empty() => {
+                Err(BattleshipError::NotYourTurn)
+            }
+            Some(_) => Ok(()),
+        }
+    }
+
+    // Verifies one shot's proof against the target's board and records it
+    fn settle_shot(
+        env: &Env,
+        game_id: u32,
+        state: &mut GameState,
+        shooter: &Address,
+        shot: Shot,
+        proof: &Groth16Proof,
+    ) -> Result<(), BattleshipError> {
+        Self::require_shot_turn(state, shooter)?;
+        let (target, board_hash, mut shots) =
+            Self::prepare_shots(env, game_id, state, shooter, &vec![env, shot.clone()])?;
+
+        // ─── Verify Groth16 ZK Proof ───
+        let vk: VerificationKey = env
             .storage()
+            .instance()
+            .get(&DataKey::VerificationKey)
+            .ok_or(BattleshipError::NotInitialized)?;
+        let pub_signals = vec![
+            env,
+            Fr::from_bytes(board_hash),
+            Self::field(env, shot.index),
+            Self::field(env, shot.result),
+            Self::field(env, shot.sunk),
+            Self::field(env, Self::hit_mask(&shots)),
+        ];
+
+        let valid = Self::verify_groth16(env, &vk, proof, &pub_signals)?;
+        if !valid {
+            return Err(BattleshipError::ProofVerificationFailed);
+        }
+
+        Self::record_shot(env, game_id, state, shooter, &mut shots, shot)?;
+        env.storage()
             .persistent()
-            .get::<_, bool>(&DataKey::GameStarted)
-            .unwrap_or(false);
-        let ended = env
+            .set(&DataKey::ShotsAt(game_id, target), &shots);
+        Ok(())
+    }
+
+    // Verifies a salvo's single proof against the target's board and records
+    // every shot in order
+    fn settle_salvo(
+        env: &Env,
+        game_id: u32,
+        state: &mut GameState,
+        shooter: &Address,
+        salvo: &Vec<Shot>,
+        proof: &Groth16Proof,
+    ) -> Result<(), BattleshipError> {
+        Self::require_shot_turn(state, shooter)?;
+        let (target, board_hash, mut shots) =
+            Self::prepare_shots(env, game_id, state, shooter, salvo)?;
+
+        // ─── Verify the batched Groth16 proof ───
+        let vk: VerificationKey = env
             .storage()
+            .instance()
+            .get(&DataKey::SalvoKey(salvo.len()))
+            .ok_or(BattleshipError::NotInitialized)?;
+        let mut pub_signals = vec![env, Fr::from_bytes(board_hash)];
+        for shot in salvo.iter() {
+            pub_signals.push_back(Self::field(env, shot.index));
+        }
+        for shot in salvo.iter() {
+            pub_signals.push_back(Self::field(env, shot.result));
+        }
+        for shot in salvo.iter() {
+            pub_signals.push_back(Self::field(env, shot.sunk));
+        }
+        pub_signals.push_back(Self::field(env, Self::hit_mask(&shots)));
+
+        let valid = Self::verify_groth16(env, &vk, proof, &pub_signals)?;
+        if !valid {
+            return Err(BattleshipError::ProofVerificationFailed);
+        }
+
+        for shot in salvo.iter() {
+            Self::record_shot(env, game_id, state, shooter, &mut shots, shot)?;
+        }
+        env.storage()
             .persistent()
-            .get::<_, bool>(&DataKey::GameEnded)
-            .unwrap_or(false);
-        (started, ended)
+            .set(&DataKey::ShotsAt(game_id, target), &shots);
+        Ok(())
+    }
+
+    // Pairs each cell with its claimed result and sunk ship
+    fn zip_shots(env: &Env, indices: &Vec<u32>, results: &Vec<u32>, sunk: &Vec<u32>) -> Vec<Shot> {
+        let mut shots = vec![env];
+        for i in 0..indices.len() {
+            shots.push_back(Shot {
+                index: indices.get_unchecked(i),
+                result: results.get_unchecked(i),
+                sunk: sunk.get_unchecked(i),
+            });
+        }
+        shots
+    }
+
+    // Bitmask of the cells hit so far, the `prior_hits` proof input
+    fn hit_mask(shots: &Vec<Shot>) -> u32 {
+        shots
+            .iter()
+            .filter(|shot| shot.result == 1)
+            .fold(0, |mask, shot| mask | 1 << shot.index)
+    }
+
+    // Applies a proven shot: counters, the audit log, events, and the win
+    // condition once the target's whole fleet is sunk
+    fn record_shot(
+        env: &Env,
+        game_id: u32,
+        state: &mut GameState,
+        shooter: &Address,
+        shots: &mut Vec<Shot>,
+        shot: Shot,
+    ) -> Result<(), BattleshipError> {
+        let fleet_size = Self::fleet(env)?.len();
+        let (side, target) = state.sides_mut(shooter)?;
+
+        // Update hit count if it's a hit
+        if shot.result == 1 {
+            side.hits += 1;
+        }
+
+        // Record the shot against the target's board for the post-game audit
+        shots.push_back(shot.clone());
+
+        // Emit shot event
+        env.events().publish(
+            (symbol_short!("shot"), symbol_short!("fire")),
+            (game_id, shooter.clone(), shot.index, shot.result),
+        );
+
+        log!(
+            env,
+            "Shot fired: index={}, result={}",
+            shot.index,
+            shot.result
+        );
+
+        let mut fleet_sunk = false;
+        if shot.sunk != 0 {
+            target.sunk.push_back(shot.sunk);
+            env.events().publish(
+                (symbol_short!("ship_sunk"), game_id),
+                (target.address.clone(), shooter.clone(), shot.sunk),
+            );
+            fleet_sunk = target.sunk.len() == fleet_size;
+        }
+
+        let target = target.address.clone();
+        state.total_shots += 1;
+        Self::start_clock(env, state, &target);
+
+        // Win condition: the whole fleet is sunk
+        if fleet_sunk {
+            Self::finish(env, game_id, state, Some(shooter.clone()));
+        }
+        Ok(())
+    }
+
+    // Shots `shooter` must fire in their next salvo
+    fn salvo_size(env: &Env, state: &GameState, shooter: &Address) -> Result<u32, BattleshipError> {
+        let GameMode::Salvo(size) = state.mode else {
+            return Err(BattleshipError::WrongGameMode);
+        };
+        Ok(match size {
+            SalvoSize::Fixed(shots) => shots,
+            SalvoSize::SurvivingShips => {
+                Self::fleet(env)?.len() - state.player(shooter)?.sunk.len()
+            }
+        })
+    }
+
+    fn fleet(env: &Env) -> Result<Vec<u32>, BattleshipError> {
+        env.storage()
+            .instance()
+            .get(&DataKey::Fleet)
+            .ok_or(BattleshipError::NotInitialized)
+    }
+
+    fn field(env: &Env, value: u32) -> Fr {
+        Fr::from_u256(U256::from_u32(env, value))
+    }
+
+    // (ships sunk, hits) landed by `player` on `opponent`'s board
+    fn score(player: &PlayerState, opponent: &PlayerState) -> (u32, u32) {
+        (opponent.sunk.len(), player.hits)
+    }
+
+    fn finish(env: &Env, game_id: u32, state: &mut GameState, winner: Option<Address>) {
+        Self::close(env, game_id, state, winner.as_ref(), true);
+
+        match winner {
+            Some(winner) => {
+                Self::report_winner(env, game_id, state, &winner);
+                env.events().publish(
+                    (symbol_short!("game"), symbol_short!("end")),
+                    (game_id, winner),
+                )
+            }
+            None => env.events().publish(
+                (symbol_short!("game"), symbol_short!("draw")),
+                game_id,
+            ),
+        }
+
+        log!(env, "Game ended! Winner determined.");
+    }
+
+    // The hub only knows wins, so draws and cancelled games are not reported
+    fn report_winner(env: &Env, game_id: u32, state: &GameState, winner: &Address) {
+        let Some(hub) = Self::get_game_hub(env.clone()) else {
+            return;
+        };
+        let Some(session_id) = state.hub_session else {
+            return;
+        };
+        let player1_won = *winner == state.player1.address;
+        let result = GameHubClient::new(env, &hub).try_end_game(&session_id, &player1_won);
+        if result.is_err() {
+            Self::hub_failed(env, game_id, symbol_short!("end"));
+        }
+    }
+
+    fn hub_failed(env: &Env, game_id: u32, call: Symbol) {
+        log!(env, "Game Hub call failed: game={}", game_id);
+        env.events()
+            .publish((symbol_short!("hub"), symbol_short!("failed")), (game_id, call));
+    }
+
+    // Stores the winner, marks the game ended and pays out the escrow,
+    // less the protocol fee when `charge_fee` is set
+    fn close(
+        env: &Env,
+        game_id: u32,
+        state: &mut GameState,
+        winner: Option<&Address>,
+        charge_fee: bool,
+    ) {
+        if let Some(winner) = winner {
+            state.winner = Some(winner.clone());
+        }
+        state.status = GameStatus::Ended;
+        Self::pay_out(env, game_id, state, winner, charge_fee);
+    }
+
+    // The winner takes every deposit; without one, each player gets their own back.
+    // The fee comes out of each deposit and stays in the contract until withdrawn.
+    fn pay_out(
+        env: &Env,
+        game_id: u32,
+        state: &mut GameState,
+        winner: Option<&Address>,
+        charge_fee: bool,
+    ) {
+        let Some(wager) = state.wager() else {
+            return;
+        };
+        let (mut d1, mut d2) = (state.player1.deposit, state.player2.deposit);
+        (state.player1.deposit, state.player2.deposit) = (0, 0);
+        if charge_fee && state.fee_bps > 0 {
+            let (f1, f2) = (Self::fee_on(d1, state.fee_bps), Self::fee_on(d2, state.fee_bps));
+            (d1, d2) = (d1 - f1, d2 - f2);
+            Self::collect_fee(env, game_id, &wager.token, f1 + f2);
+        }
+        let (p1, p2) = (state.player1.address.clone(), state.player2.address.clone());
+        let payouts = match winner {
+            Some(winner) => vec![env, (winner.clone(), d1 + d2)],
+            None => vec![env, (p1, d1), (p2, d2)],
+        };
+
+        let token = token::Client::new(env, &wager.token);
+        for (player, amount) in payouts {
+            if amount == 0 {
+                continue;
+            }
+            token.transfer(&env.current_contract_address(), &player, &amount);
+            env.events().publish(
+                (symbol_short!("wager"), symbol_short!("payout")),
+                (game_id, player, amount),
+            );
+        }
+    }
+
+    // Rounded down, so a pot too small for the fee pays none
+    fn fee_on(amount: i128, fee_bps: u32) -> i128 {
+        amount / BPS * fee_bps as i128 + amount % BPS * fee_bps as i128 / BPS
+    }
+
+    fn collect_fee(env: &Env, game_id: u32, token: &Address, fee: i128) {
+        if fee == 0 {
+            return;
+        }
+        let fees_key = DataKey::Fees(token.clone());
+        let collected: i128 = env.storage().persistent().get(&fees_key).unwrap_or(0);
+        env.storage().persistent().set(&fees_key, &(collected + fee));
+        Self::extend(env, &fees_key);
+        env.events().publish(
+            (symbol_short!("fee"), symbol_short!("collect")),
+            (game_id, token.clone(), fee),
+        );
+    }
+
+    fn extend_instance(env: &Env) {
+        env.storage()
+            .instance()
+            .extend_ttl(TTL_THRESHOLD, TTL_BUMP);
     }
 
-    pub fn get_winner(env: Env) -> Option<Address> {
-        env.storage().persistent().get(&DataKey::Winner)
+    fn extend(env: &Env, key: &DataKey) {
+        if env.storage().persistent().has(key) {
+            env.storage()
+                .persistent()
+                .extend_ttl(key, TTL_THRESHOLD, TTL_BUMP);
+        }
+    }
+
+    // Extends the instance and every persistent entry of the game. Called at
+    // the end of each move, so the game lives TTL_BUMP ledgers past the last one.
+    fn extend_game(env: &Env, game_id: u32, state: &GameState) {
+        Self::extend_instance(env);
+        Self::extend(env, &DataKey::Game(game_id));
+        for player in [&state.player1.address, &state.player2.address] {
+            Self::extend(env, &DataKey::ShotsAt(game_id, player.clone()));
+            if state.is_over() {
+                Self::extend(env, &DataKey::RevealedBoard(game_id, player.clone()));
+            }
+        }
+    }
+
+    // Puts `player` on the clock for the next action, for claim_timeout
+    fn start_clock(env: &Env, state: &mut GameState, player: &Address) {
+        state.on_clock = Some(player.clone());
+        state.clock_start = env.ledger().sequence();
+        state.called = vec![env];
+    }
+
+    // Poseidon2(board || salt) as the circuit computes it, in the big-endian
+    // layout `commit_board` stores. `None` if the salt is not a field element.
+    fn board_commitment(board: &Vec<u32>, salt: &BytesN<32>) -> Option<[u8; 32]> {
+        let mut inputs = [poseidon2::Fr::ZERO; BOARD_CELLS as usize + 1];
+        for (input, cell) in inputs.iter_mut().zip(board.iter()) {
+            *input = poseidon2::Fr::from_u64(cell as u64);
+        }
+        inputs[BOARD_CELLS as usize] = poseidon2::Fr::from_be_bytes(&salt.to_array())?;
+        Some(poseidon2::hash(&inputs).to_be_bytes())
+    }
+
+    // A legal board holds water (0) or ship ids from the fleet, and ship `id`
+    // covers exactly `fleet[id - 1]` cells in one unbroken row or column.
+    fn is_legal_fleet(board: &Vec<u32>, fleet: &Vec<u32>) -> bool {
+        if board.iter().any(|cell| cell > fleet.len()) {
+            return false;
+        }
+        for (ship, length) in fleet.iter().enumerate() {
+            let id = ship as u32 + 1;
+            // Cells come out in ascending order, so the first and last bound the ship
+            let mut cells = board
+                .iter()
+                .enumerate()
+                .filter(|(_, cell)| *cell == id)
+                .map(|(i, _)| i as u32);
+            let Some(first) = cells.next() else {
+                return false;
+            };
+            let (mut count, mut last) = (1, first);
+            let (mut same_row, mut same_col) = (true, true);
+            for cell in cells {
+                count += 1;
+                last = cell;
+                same_row &= cell / GRID_SIZE == first / GRID_SIZE;
+                same_col &= cell % GRID_SIZE == first % GRID_SIZE;
+            }
+            let horizontal = same_row && last - first == length - 1;
+            let vertical = same_col && last - first == (length - 1) * GRID_SIZE;
+            if count != length || !(horizontal || vertical) {
+                return false;
+            }
+        }
+        true
+    }
+
+    // Replays the shots in order: each result must match the cell, and a hit
+    // sinks the cell's ship exactly when it covers the ship's last unhit cell
+    fn shots_match(board: &Vec<u32>, shots: &Vec<Shot>) -> bool {
+        let mut hit_mask = 0u32;
+        for shot in shots.iter() {
+            let ship = board.get(shot.index).unwrap_or(0);
+            if shot.result != (ship != 0) as u32 {
+                return false;
+            }
+            if ship == 0 {
+                continue;
+            }
+            hit_mask |= 1 << shot.index;
+            let sunk = board
+                .iter()
+                .enumerate()
+                .all(|(i, cell)| cell != ship || hit_mask & (1 << i) != 0);
+            if shot.sunk != if sunk { ship } else { 0 } {
+                return false;
+            }
+        }
+        true
     }
 
     // ─── Internal: Groth16 proof verification ───
@@ -439,19 +1949,7 @@ impl ZkBattleship {
         pub_signals: &Vec<Fr>,
     ) -> Result<bool, BattleshipError> {
         let bls = env.crypto().bls12_381();
-
-        // Compute vk_x = ic[0] + sum(pub_signals[i] * ic[i+1])
-        if pub_signals.len() + 1 != vk.ic.len() {
-            return Err(BattleshipError::MalformedProof);
-        }
-
-        let mut vk_x = vk.ic.get(0).unwrap();
-        for i in 0..pub_signals.len() {
-            let s = pub_signals.get(i).unwrap();
-            let v = vk.ic.get(i + 1).unwrap();
-            let prod = bls.g1_mul(&v, &s);
-            vk_x = bls.g1_add(&vk_x, &prod);
-        }
+        let vk_x = Self::public_input_point(env, vk, pub_signals)?;
 
         // Pairing check: e(-A, B) * e(alpha, beta) * e(vk_x, gamma) * e(C, delta) == 1
         let neg_a = -proof.a.clone();
@@ -466,6 +1964,86 @@ impl ZkBattleship {
 
         Ok(bls.pairing_check(vp1, vp2))
     }
+
+    // Randomized batch verification of N proofs under one key, in a single
+    // pairing check with N + 3 pairs instead of 4N. Each proof i is weighted by a
+    // random r_i from the ledger PRNG:
+    //   prod e(-r_i*A_i, B_i) * e(sum(r_i)*alpha, beta)
+    //     * e(sum(r_i*vk_x_i), gamma) * e(sum(r_i*C_i), delta) == 1
+    // A batch holding any invalid proof passes only with negligible probability.
+    // The vk_x and C sums are folded into two multi-scalar multiplications.
+    fn verify_batch(
+        env: &Env,
+        vk: &VerificationKey,
+        proofs: &Vec<Groth16Proof>,
+        pub_signals: &Vec<Vec<Fr>>,
+    ) -> Result<bool, BattleshipError> {
+        if proofs.is_empty() || proofs.len() != pub_signals.len() {
+            return Err(BattleshipError::MalformedProof);
+        }
+        let bls = env.crypto().bls12_381();
+        let zero = Fr::from_u256(U256::from_u32(env, 0));
+
+        // ic_weights[j] ends up as sum(r_i * s_ij), with s_i0 = 1
+        let mut ic_weights = vec![env];
+        for _ in 0..vk.ic.len() {
+            ic_weights.push_back(zero.clone());
+        }
+        let mut vp1 = vec![env];
+        let mut vp2 = vec![env];
+        let mut c_points = vec![env];
+        let mut r_values = vec![env];
+        let mut r_sum = zero;
+        for (proof, signals) in proofs.iter().zip(pub_signals.iter()) {
+            if signals.len() + 1 != vk.ic.len() {
+                return Err(BattleshipError::MalformedProof);
+            }
+            // 128-bit weights keep the soundness error far below any forgery budget
+            let (hi, lo) = (env.prng().gen::<u64>(), env.prng().gen::<u64>());
+            let r = Fr::from_u256(U256::from_parts(env, 0, 0, hi, lo));
+
+            ic_weights.set(0, ic_weights.get_unchecked(0) + r.clone());
+            for (j, signal) in signals.iter().enumerate() {
+                let j = j as u32 + 1;
+                ic_weights.set(j, ic_weights.get_unchecked(j) + r.clone() * signal);
+            }
+            vp1.push_back(-bls.g1_mul(&proof.a, &r));
+            vp2.push_back(proof.b);
+            c_points.push_back(proof.c);
+            r_sum = r_sum + r.clone();
+            r_values.push_back(r);
+        }
+
+        vp1.push_back(bls.g1_mul(&vk.alpha, &r_sum));
+        vp2.push_back(vk.beta.clone());
+        vp1.push_back(bls.g1_msm(vk.ic.clone(), ic_weights));
+        vp2.push_back(vk.gamma.clone());
+        vp1.push_back(bls.g1_msm(c_points, r_values));
+        vp2.push_back(vk.delta.clone());
+
+        Ok(bls.pairing_check(vp1, vp2))
+    }
+
+    // vk_x = ic[0] + sum(pub_signals[i] * ic[i+1])
+    fn public_input_point(
+        env: &Env,
+        vk: &VerificationKey,
+        pub_signals: &Vec<Fr>,
+    ) -> Result<G1Affine, BattleshipError> {
+        let bls = env.crypto().bls12_381();
+        if pub_signals.len() + 1 != vk.ic.len() {
+            return Err(BattleshipError::MalformedProof);
+        }
+
+        let mut vk_x = vk.ic.get(0).unwrap();
+        for i in 0..pub_signals.len() {
+            let s = pub_signals.get(i).unwrap();
+            let v = vk.ic.get(i + 1).unwrap();
+            let prod = bls.g1_mul(&v, &s);
+            vk_x = bls.g1_add(&vk_x, &prod);
+        }
+        Ok(vk_x)
+    }
 }
 
 mod test;
@@ -1,22 +1,1458 @@
 #![cfg(test)]
 
-// Tests would go here. Due to the cross-contract call to Game Hub,
-// full integration tests require deploying both contracts.
-// For unit testing, we would mock the Game Hub contract.
-//
-// Example test structure:
-//
-// use soroban_sdk::{testutils::Address as _, Address, Env};
-// use crate::{ZkBattleship, ZkBattleshipClient};
-//
-// #[test]
-// fn test_game_lifecycle() {
-//     let env = Env::default();
-//     let contract_id = env.register_contract(None, ZkBattleship);
-//     let client = ZkBattleshipClient::new(&env, &contract_id);
-//
-//     let player1 = Address::generate(&env);
-//     let player2 = Address::generate(&env);
-//
-//     // Start game, commit boards, shoot, end game...
-// }
+use soroban_sdk::{
+    crypto::bls12_381::{Fr, G1Affine, G2Affine},
+    testutils::{
+        storage::{Instance as _, Persistent as _},
+        Address as _, Events, Ledger, MockAuth, MockAuthInvoke,
+    },
+    token::{StellarAssetClient, TokenClient},
+    symbol_short, vec, Address, Bytes, BytesN, Env, IntoVal, Symbol, Vec, U256,
+};
+
+use crate::{
+    BattleshipError, DataKey, GameMode, Groth16Proof, SalvoSize, Shot, VerificationKey, Wager,
+    ZkBattleship, ZkBattleshipClient,
+};
+use game_hub::{GameHub, GameHubClient};
+use mock_game_hub::{MockGameHub, MockGameHubClient};
+use zk_poseidon2 as poseidon2;
+
+// Ship 1 is three cells long, ship 2 two
+const FLEET: [u32; 2] = [3, 2];
+const MAX_SHIPS: usize = 5;
+
+// (claimed_result, sunk_ship) for a shot
+const MISS: (u32, u32) = (0, 0);
+const HIT: (u32, u32) = (1, 0);
+
+fn sinks(ship: u32) -> (u32, u32) {
+    (1, ship)
+}
+
+// Test keys are built from a known trapdoor so proofs can be produced without a prover:
+// alpha = a*P, beta = b*Q, gamma = delta = Q, ic[i] = k_i*P.
+// A = x*P, B = Q, C = c*P then passes the pairing check exactly when
+// x = a*b + (k_0 + sum(s_i * k_i)) + c for the public signals s.
+const TRAPDOOR_A: u32 = 2;
+const TRAPDOOR_B: u32 = 3;
+const TRAPDOOR_C: u32 = 7;
+
+fn fr(env: &Env, value: u32) -> Fr {
+    Fr::from_u256(U256::from_u32(env, value))
+}
+
+fn base_points(env: &Env) -> (G1Affine, G2Affine) {
+    let bls = env.crypto().bls12_381();
+    let dst = Bytes::from_slice(env, b"ZK-BATTLESHIP-TEST");
+    let p = bls.hash_to_g1(&Bytes::from_slice(env, b"g1"), &dst);
+    let q = bls.hash_to_g2(&Bytes::from_slice(env, b"g2"), &dst);
+    (p, q)
+}
+
+// `ic[i]` uses k_i = seed + i, so keys with different seeds reject each other's proofs
+fn test_vk(env: &Env, seed: u32, inputs: u32) -> VerificationKey {
+    let bls = env.crypto().bls12_381();
+    let (p, q) = base_points(env);
+    let mut ic = Vec::new(env);
+    for i in 0..=inputs {
+        ic.push_back(bls.g1_mul(&p, &fr(env, seed + i)));
+    }
+    VerificationKey {
+        alpha: bls.g1_mul(&p, &fr(env, TRAPDOOR_A)),
+        beta: bls.g2_mul(&q, &fr(env, TRAPDOOR_B)),
+        gamma: q.clone(),
+        delta: q,
+        ic,
+    }
+}
+
+fn prove(env: &Env, seed: u32, pub_signals: &Vec<Fr>) -> Groth16Proof {
+    let bls = env.crypto().bls12_381();
+    let (p, q) = base_points(env);
+    let mut x = fr(env, TRAPDOOR_A * TRAPDOOR_B + TRAPDOOR_C + seed);
+    for i in 0..pub_signals.len() {
+        x = x + pub_signals.get(i).unwrap() * fr(env, seed + i + 1);
+    }
+    Groth16Proof {
+        a: bls.g1_mul(&p, &x),
+        b: q,
+        c: bls.g1_mul(&p, &fr(env, TRAPDOOR_C)),
+    }
+}
+
+const SHOT_SEED: u32 = 11;
+const PLACEMENT_SEED: u32 = 23;
+const SALVO_SEED: u32 = 31;
+
+// A salvo of `shots` has 3 * shots + 2 public signals
+fn salvo_seed(shots: u32) -> u32 {
+    SALVO_SEED + 10 * shots
+}
+
+fn board_hash(env: &Env, tag: u8) -> BytesN<32> {
+    BytesN::from_array(env, &[tag; 32])
+}
+
+fn placement_proof(env: &Env, hash: &BytesN<32>, fleet: &[u32]) -> Groth16Proof {
+    let mut signals = vec![env, Fr::from_bytes(hash.clone())];
+    for ship in 0..MAX_SHIPS {
+        signals.push_back(fr(env, fleet.get(ship).copied().unwrap_or(0)));
+    }
+    prove(env, PLACEMENT_SEED, &signals)
+}
+
+fn fleet_lengths(env: &Env) -> Vec<u32> {
+    Vec::from_slice(env, &FLEET)
+}
+
+fn setup(env: &Env) -> (ZkBattleshipClient<'_>, u32, Address, Address) {
+    env.mock_all_auths();
+    let contract_id = env.register(ZkBattleship, ());
+    let client = ZkBattleshipClient::new(env, &contract_id);
+    client.init(
+        &Address::generate(env),
+        &test_vk(env, SHOT_SEED, 5),
+        &test_vk(env, PLACEMENT_SEED, 1 + MAX_SHIPS as u32),
+        &fleet_lengths(env),
+    );
+    for shots in 1..=2 {
+        client.set_salvo_key(&shots, &test_vk(env, salvo_seed(shots), 3 * shots + 2));
+    }
+
+    let player1 = Address::generate(env);
+    let player2 = Address::generate(env);
+    let game = client.start_game(&player1, &player2, &None);
+    client.accept_game(&game, &player2);
+    (client, game, player1, player2)
+}
+
+// Both boards committed to their real Poseidon2 commitments
+fn setup_committed_with<'a>(
+    env: &'a Env,
+    board1: &Vec<u32>,
+    board2: &Vec<u32>,
+) -> (ZkBattleshipClient<'a>, u32, Address, Address) {
+    let (client, game, player1, player2) = setup(env);
+    for (player, board) in [(&player1, board1), (&player2, board2)] {
+        let hash = commitment(env, board, &salt(env));
+        client.commit_board(&game, player, &hash, &placement_proof(env, &hash, &FLEET));
+    }
+    (client, game, player1, player2)
+}
+
+// A salvo game between fresh players, with both fleets committed
+fn setup_salvo(env: &Env, size: SalvoSize) -> (ZkBattleshipClient<'_>, u32, Address, Address) {
+    let (client, _, player1, player2) = setup(env);
+    let game = client.start_salvo(&player1, &player2, &size, &None);
+    client.accept_game(&game, &player2);
+    for player in [&player1, &player2] {
+        let hash = commitment(env, &fleet(env), &salt(env));
+        client.commit_board(&game, player, &hash, &placement_proof(env, &hash, &FLEET));
+    }
+    (client, game, player1, player2)
+}
+
+// Proves every claim of the salvo against the target's board in one go
+fn fire_salvo(
+    env: &Env,
+    client: &ZkBattleshipClient,
+    game: u32,
+    shooter: &Address,
+    target: &Address,
+    salvo: &[(u32, (u32, u32))],
+) -> Result<u32, BattleshipError> {
+    let (mut indices, mut results, mut sunk) = (Vec::new(env), Vec::new(env), Vec::new(env));
+    for (index, (result, ship)) in salvo {
+        indices.push_back(*index);
+        results.push_back(*result);
+        sunk.push_back(*ship);
+    }
+
+    let (hash, prior_hits) = target_board(client, game, target);
+    let mut signals = vec![env, hash];
+    for values in [&indices, &results, &sunk] {
+        for value in values.iter() {
+            signals.push_back(fr(env, value));
+        }
+    }
+    signals.push_back(fr(env, prior_hits));
+
+    let proof = prove(env, salvo_seed(salvo.len() as u32), &signals);
+    match client.try_fire_salvo(&game, shooter, &indices, &results, &sunk, &proof) {
+        Ok(hits) => Ok(hits.unwrap()),
+        Err(err) => Err(err.unwrap()),
+    }
+}
+
+// Consecutive turns with their own proofs: claim i is fired at `targets[i % 2]` and
+// proven against the hits settled on that board before it
+fn batch(
+    env: &Env,
+    client: &ZkBattleshipClient,
+    game: u32,
+    targets: [&Address; 2],
+    claims: &[(u32, (u32, u32))],
+) -> (Vec<Shot>, Vec<Groth16Proof>) {
+    let mut boards = targets.map(|target| target_board(client, game, target));
+    let (mut shots, mut proofs) = (Vec::new(env), Vec::new(env));
+    for (i, (index, (result, sunk))) in claims.iter().enumerate() {
+        let (hash, hits) = &mut boards[i % 2];
+        let signals = vec![env, hash.clone(), fr(env, *index), fr(env, *result), fr(env, *sunk), fr(env, *hits)];
+        proofs.push_back(prove(env, SHOT_SEED, &signals));
+        shots.push_back(Shot { index: *index, result: *result, sunk: *sunk });
+        if *result == 1 {
+            *hits |= 1 << index;
+        }
+    }
+    (shots, proofs)
+}
+
+const STAKE: i128 = 100;
+const BALANCE: i128 = 1_000;
+
+// A game staked in a freshly registered asset both players hold BALANCE of,
+// under a `fee_bps` protocol fee. Neither board is committed yet, so nothing is deposited.
+fn setup_wagered(
+    env: &Env,
+    fee_bps: u32,
+) -> (ZkBattleshipClient<'_>, u32, Address, Address, TokenClient<'_>) {
+    let (client, _, player1, player2) = setup(env);
+    client.set_fee(&fee_bps);
+    let asset = env.register_stellar_asset_contract_v2(Address::generate(env));
+    for player in [&player1, &player2] {
+        StellarAssetClient::new(env, &asset.address()).mint(player, &BALANCE);
+    }
+    let wager = Wager { token: asset.address(), stake: STAKE };
+    let game = client.start_game(&player1, &player2, &Some(wager));
+    client.accept_game(&game, &player2);
+    (client, game, player1, player2, TokenClient::new(env, &asset.address()))
+}
+
+fn commit_fleet(env: &Env, client: &ZkBattleshipClient, game: u32, player: &Address) {
+    let hash = commitment(env, &fleet(env), &salt(env));
+    client.commit_board(&game, player, &hash, &placement_proof(env, &hash, &FLEET));
+}
+
+fn setup_committed(env: &Env) -> (ZkBattleshipClient<'_>, u32, Address, Address) {
+    setup_committed_with(env, &fleet(env), &fleet(env))
+}
+
+// Proves `claim` against the target's committed board and the hits already on it
+fn shoot(
+    env: &Env,
+    client: &ZkBattleshipClient,
+    game: u32,
+    shooter: &Address,
+    target: &Address,
+    index: u32,
+    claim: (u32, u32),
+) {
+    let (result, sunk) = claim;
+    let proof = prove(env, SHOT_SEED, &shot_signals(env, client, game, target, index, claim));
+    client.shoot(&game, shooter, &index, &result, &sunk, &proof);
+}
+
+fn shot_signals(
+    env: &Env,
+    client: &ZkBattleshipClient,
+    game: u32,
+    target: &Address,
+    index: u32,
+    (result, sunk): (u32, u32),
+) -> Vec<Fr> {
+    let (hash, prior_hits) = target_board(client, game, target);
+    vec![env, hash, fr(env, index), fr(env, result), fr(env, sunk), fr(env, prior_hits)]
+}
+
+// The target's board hash and the bitmask of cells already hit on it
+fn target_board(client: &ZkBattleshipClient, game: u32, target: &Address) -> (Fr, u32) {
+    let hash = client.get_board_hash(&game, target).unwrap();
+    let prior_hits = client
+        .get_shots_at(&game, target)
+        .iter()
+        .filter(|shot| shot.result == 1)
+        .fold(0, |mask, shot| mask | 1 << shot.index);
+    (Fr::from_bytes(hash), prior_hits)
+}
+
+// Ship 1 across the top row at 0..2, ship 2 down the right edge at 9 and 14
+fn fleet(env: &Env) -> Vec<u32> {
+    let mut board = Vec::new(env);
+    for i in 0..25 {
+        board.push_back(match i {
+            0..=2 => 1,
+            9 | 14 => 2,
+            _ => 0,
+        });
+    }
+    board
+}
+
+// Salt 42, as in the circuit's own tests
+fn salt(env: &Env) -> BytesN<32> {
+    let mut salt = [0; 32];
+    salt[31] = 42;
+    BytesN::from_array(env, &salt)
+}
+
+fn commitment(env: &Env, board: &Vec<u32>, salt: &BytesN<32>) -> BytesN<32> {
+    let mut inputs = [poseidon2::Fr::ZERO; 26];
+    for (input, cell) in inputs.iter_mut().zip(board.iter()) {
+        *input = poseidon2::Fr::from_u64(cell as u64);
+    }
+    inputs[25] = poseidon2::Fr::from_be_bytes(&salt.to_array()).unwrap();
+    BytesN::from_array(env, &poseidon2::hash(&inputs).to_be_bytes())
+}
+
+#[test]
+fn test_commit_board_accepts_a_valid_fleet_proof() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup(&env);
+    let hash = board_hash(&env, 1);
+
+    client.commit_board(&game, &player1, &hash, &placement_proof(&env, &hash, &FLEET));
+
+    assert_eq!(client.get_fleet(), fleet_lengths(&env));
+    assert_eq!(client.get_ship_cells(), 5);
+    assert_eq!(client.get_board_hash(&game, &player1), Some(hash.clone()));
+    assert_eq!(
+        client.try_commit_board(&game, &player1, &hash, &placement_proof(&env, &hash, &FLEET)),
+        Err(Ok(BattleshipError::BoardAlreadyCommitted))
+    );
+    let other = board_hash(&env, 2);
+    client.commit_board(&game, &player2, &other, &placement_proof(&env, &other, &FLEET));
+}
+
+#[test]
+fn test_commit_board_rejects_a_short_fleet() {
+    let env = Env::default();
+    let (client, game, player1, _) = setup(&env);
+    let hash = board_hash(&env, 1);
+
+    // An all-water board can only prove an empty fleet
+    assert_eq!(
+        client.try_commit_board(&game, &player1, &hash, &placement_proof(&env, &hash, &[])),
+        Err(Ok(BattleshipError::ProofVerificationFailed))
+    );
+}
+
+#[test]
+fn test_commit_board_rejects_a_proof_for_another_board() {
+    let env = Env::default();
+    let (client, game, player1, _) = setup(&env);
+    let proven = board_hash(&env, 1);
+
+    assert_eq!(
+        client.try_commit_board(&game, &player1, &board_hash(&env, 2), &placement_proof(&env, &proven, &FLEET)),
+        Err(Ok(BattleshipError::ProofVerificationFailed))
+    );
+}
+
+#[test]
+fn test_commit_board_rejects_a_shot_key_proof() {
+    let env = Env::default();
+    let (client, game, player1, _) = setup(&env);
+    let hash = board_hash(&env, 1);
+    let mut signals = vec![&env, Fr::from_bytes(hash.clone())];
+    for length in [3, 2, 0, 0, 0] {
+        signals.push_back(fr(&env, length));
+    }
+
+    assert_eq!(
+        client.try_commit_board(&game, &player1, &hash, &prove(&env, SHOT_SEED, &signals)),
+        Err(Ok(BattleshipError::ProofVerificationFailed))
+    );
+}
+
+#[test]
+fn test_games_are_kept_apart() {
+    let env = Env::default();
+    let (client, game1, player1, player2) = setup_committed(&env);
+    let player3 = Address::generate(&env);
+    let game2 = client.start_game(&player1, &player3, &None);
+    client.accept_game(&game2, &player3);
+
+    shoot(&env, &client, game1, &player1, &player2, 0, HIT);
+
+    assert_eq!((game1, game2), (1, 2));
+    assert_eq!(client.get_hits(&game1, &player1), 1);
+    assert_eq!(client.get_hits(&game2, &player1), 0);
+    let hash = board_hash(&env, 3);
+    assert_eq!(
+        client.try_commit_board(&game2, &player2, &hash, &placement_proof(&env, &hash, &FLEET)),
+        Err(Ok(BattleshipError::InvalidPlayer))
+    );
+}
+
+#[test]
+fn test_honest_reveal_keeps_the_result() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup_committed(&env);
+    for (index, claim) in [(0, HIT), (3, MISS), (14, HIT)] {
+        shoot(&env, &client, game, &player1, &player2, 20 + index % 5, MISS);
+        shoot(&env, &client, game, &player2, &player1, index, claim);
+    }
+
+    assert_eq!(
+        client.try_reveal_board(&game, &player1, &fleet(&env), &salt(&env)),
+        Err(Ok(BattleshipError::GameNotEnded))
+    );
+    let winner = client.end_game(&game, &player1);
+
+    assert!(client.reveal_board(&game, &player1, &fleet(&env), &salt(&env)));
+    assert_eq!(client.get_winner(&game), winner);
+    assert!(!client.is_cheater(&game, &player1));
+    assert_eq!(client.get_revealed_board(&game, &player1), Some(fleet(&env)));
+    assert_eq!(
+        client.try_reveal_board(&game, &player1, &fleet(&env), &salt(&env)),
+        Err(Ok(BattleshipError::BoardAlreadyRevealed))
+    );
+}
+
+#[test]
+fn test_false_miss_reverses_the_outcome() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup_committed(&env);
+    shoot(&env, &client, game, &player1, &player2, 5, HIT);
+    // Cell 9 holds a ship, but player 1 answered the shot as a miss
+    shoot(&env, &client, game, &player2, &player1, 9, MISS);
+    assert_eq!(client.end_game(&game, &player1), Some(player1.clone()));
+
+    assert!(!client.reveal_board(&game, &player1, &fleet(&env), &salt(&env)));
+
+    assert!(client.is_cheater(&game, &player1));
+    assert_eq!(client.get_winner(&game), Some(player2));
+    assert_eq!(
+        client.get_shots_at(&game, &player1),
+        vec![&env, Shot { index: 9, result: 0, sunk: 0 }]
+    );
+}
+
+#[test]
+fn test_illegal_fleet_fails_the_audit() {
+    let env = Env::default();
+    // Right cell count, but ship 1 bends around a corner
+    let mut bent = fleet(&env);
+    bent.set(2, 0);
+    bent.set(8, 1);
+    // Ship 1 a cell short, with the spare cell given an id outside the fleet
+    let mut short = fleet(&env);
+    short.set(0, 0);
+    short.set(20, 3);
+    let (client, game, player1, player2) = setup_committed_with(&env, &short, &bent);
+    client.end_game(&game, &player1);
+
+    assert!(!client.reveal_board(&game, &player2, &bent, &salt(&env)));
+    assert_eq!(client.get_winner(&game), Some(player1.clone()));
+
+    // Both sides caught: nobody wins
+    assert!(!client.reveal_board(&game, &player1, &short, &salt(&env)));
+    assert_eq!(client.get_winner(&game), None);
+}
+
+#[test]
+fn test_reveal_rejects_malformed_boards() {
+    let env = Env::default();
+    let (client, game, player1, _) = setup_committed(&env);
+    client.end_game(&game, &player1);
+
+    let mut board = fleet(&env);
+    board.pop_back();
+    assert_eq!(
+        client.try_reveal_board(&game, &player1, &board, &salt(&env)),
+        Err(Ok(BattleshipError::InvalidBoard))
+    );
+}
+
+#[test]
+fn test_reveal_must_open_the_commitment() {
+    let env = Env::default();
+    let (client, game, player1, _) = setup_committed(&env);
+    client.end_game(&game, &player1);
+
+    // A different board under the right salt
+    let mut moved = fleet(&env);
+    moved.set(14, 0);
+    moved.set(19, 2);
+    assert_eq!(
+        client.try_reveal_board(&game, &player1, &moved, &salt(&env)),
+        Err(Ok(BattleshipError::CommitmentMismatch))
+    );
+    // The right board under another salt, or one that is not a field element
+    for other in [[7; 32], [0xff; 32]] {
+        assert_eq!(
+            client.try_reveal_board(&game, &player1, &fleet(&env), &BytesN::from_array(&env, &other)),
+            Err(Ok(BattleshipError::CommitmentMismatch))
+        );
+    }
+
+    assert!(client.reveal_board(&game, &player1, &fleet(&env), &salt(&env)));
+}
+
+#[test]
+fn test_init_rejects_a_bad_fleet() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = ZkBattleshipClient::new(&env, &env.register(ZkBattleship, ()));
+    let (shot_vk, placement_vk) = (test_vk(&env, SHOT_SEED, 5), test_vk(&env, PLACEMENT_SEED, 6));
+
+    for fleet in [vec![&env], vec![&env, 3, 0], vec![&env, 6], vec![&env, 1, 1, 1, 1, 1, 1]] {
+        assert_eq!(
+            client.try_init(&Address::generate(&env), &shot_vk, &placement_vk, &fleet),
+            Err(Ok(BattleshipError::InvalidFleet))
+        );
+    }
+}
+
+#[test]
+fn test_last_hit_announces_the_sunk_ship() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup_committed(&env);
+
+    shoot(&env, &client, game, &player1, &player2, 9, HIT);
+    assert_eq!(client.get_sunk_ships(&game, &player2), vec![&env]);
+    shoot(&env, &client, game, &player2, &player1, 3, MISS);
+    shoot(&env, &client, game, &player1, &player2, 14, sinks(2));
+
+    let sunk_event = (
+        client.address.clone(),
+        (Symbol::new(&env, "ship_sunk"), game).into_val(&env),
+        (player2.clone(), player1.clone(), 2u32).into_val(&env),
+    );
+    assert!(env.events().all().contains(&sunk_event));
+    assert_eq!(client.get_sunk_ships(&game, &player2), vec![&env, 2]);
+    assert_eq!(client.get_game_state(&game), (true, false));
+}
+
+#[test]
+fn test_sinking_the_whole_fleet_wins() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup_committed(&env);
+    // Player 1 sinks a ship first, but player 2 finishes the fleet off
+    let player1_shots = [(0, HIT), (1, HIT), (2, sinks(1)), (5, MISS), (6, MISS)];
+    let player2_shots = [(9, HIT), (2, HIT), (0, HIT), (1, sinks(1))];
+    for (i, (index, claim)) in player1_shots.into_iter().enumerate() {
+        shoot(&env, &client, game, &player1, &player2, index, claim);
+        if let Some((index, claim)) = player2_shots.get(i) {
+            shoot(&env, &client, game, &player2, &player1, *index, *claim);
+        }
+    }
+    assert_eq!(client.get_game_state(&game), (true, false));
+
+    shoot(&env, &client, game, &player2, &player1, 14, sinks(2));
+
+    assert_eq!(client.get_game_state(&game), (true, true));
+    assert_eq!(client.get_winner(&game), Some(player2.clone()));
+    assert_eq!(client.get_sunk_ships(&game, &player1), vec![&env, 1, 2]);
+    assert_eq!(
+        client.try_end_game(&game, &player1),
+        Err(Ok(BattleshipError::GameAlreadyEnded))
+    );
+    assert!(client.reveal_board(&game, &player1, &fleet(&env), &salt(&env)));
+}
+
+#[test]
+fn test_ending_early_counts_sunk_ships_before_hits() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup_committed(&env);
+    shoot(&env, &client, game, &player1, &player2, 9, HIT);
+    shoot(&env, &client, game, &player2, &player1, 0, HIT);
+    shoot(&env, &client, game, &player1, &player2, 14, sinks(2));
+    shoot(&env, &client, game, &player2, &player1, 1, HIT);
+    shoot(&env, &client, game, &player1, &player2, 3, MISS);
+    shoot(&env, &client, game, &player2, &player1, 9, HIT);
+
+    assert_eq!(client.end_game(&game, &player2), Some(player1));
+}
+
+#[test]
+fn test_ending_early_needs_both_players() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup_committed(&env);
+    shoot(&env, &client, game, &player1, &player2, 0, HIT);
+
+    assert_eq!(
+        client.try_end_game(&game, &Address::generate(&env)),
+        Err(Ok(BattleshipError::InvalidPlayer))
+    );
+    assert_eq!(client.end_game(&game, &player1), Some(player1.clone()));
+    let auths = env.auths();
+    assert!(auths.iter().any(|(address, _)| *address == player1));
+    assert!(auths.iter().any(|(address, _)| *address == player2));
+}
+
+#[test]
+fn test_shot_claims_are_checked() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup_committed(&env);
+    shoot(&env, &client, game, &player1, &player2, 9, HIT);
+    shoot(&env, &client, game, &player2, &player1, 3, MISS);
+
+    let attempt = |index: u32, claim: (u32, u32)| {
+        let proof = prove(&env, SHOT_SEED, &shot_signals(&env, &client, game, &player2, index, claim));
+        client.try_shoot(&game, &player1, &index, &claim.0, &claim.1, &proof)
+    };
+    assert_eq!(attempt(9, HIT), Err(Ok(BattleshipError::CellAlreadyShot)));
+    assert_eq!(attempt(14, (2, 0)), Err(Ok(BattleshipError::InvalidShotResult)));
+    assert_eq!(attempt(14, (0, 2)), Err(Ok(BattleshipError::InvalidShotResult)));
+    assert_eq!(attempt(14, sinks(3)), Err(Ok(BattleshipError::InvalidShotResult)));
+
+    // The proof is bound to the sunk claim the contract records
+    let proof = prove(&env, SHOT_SEED, &shot_signals(&env, &client, game, &player2, 14, HIT));
+    assert_eq!(
+        client.try_shoot(&game, &player1, &14, &1, &2, &proof),
+        Err(Ok(BattleshipError::ProofVerificationFailed))
+    );
+}
+
+#[test]
+fn test_false_sunk_claims_fail_the_audit() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup_committed(&env);
+    // Ship 2 goes down without being announced, and ship 1 is announced sunk
+    // with a cell still afloat
+    shoot(&env, &client, game, &player1, &player2, 9, HIT);
+    shoot(&env, &client, game, &player2, &player1, 0, HIT);
+    shoot(&env, &client, game, &player1, &player2, 14, HIT);
+    shoot(&env, &client, game, &player2, &player1, 1, sinks(1));
+    client.end_game(&game, &player1);
+
+    assert!(!client.reveal_board(&game, &player1, &fleet(&env), &salt(&env)));
+    assert!(!client.reveal_board(&game, &player2, &fleet(&env), &salt(&env)));
+    assert_eq!(client.get_winner(&game), None);
+}
+
+#[test]
+fn test_salvo_resolves_in_order() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup_salvo(&env, SalvoSize::Fixed(2));
+    assert_eq!(client.get_salvo_size(&game, &player1), 2);
+
+    // The second shot sinks ship 2 thanks to the first
+    assert_eq!(fire_salvo(&env, &client, game, &player1, &player2, &[(9, HIT), (14, sinks(2))]), Ok(2));
+    assert_eq!(fire_salvo(&env, &client, game, &player2, &player1, &[(3, MISS), (2, HIT)]), Ok(1));
+
+    assert_eq!(client.get_sunk_ships(&game, &player2), vec![&env, 2]);
+    assert_eq!(
+        client.get_shots_at(&game, &player2),
+        vec![&env, Shot { index: 9, result: 1, sunk: 0 }, Shot { index: 14, result: 1, sunk: 2 }]
+    );
+    client.end_game(&game, &player1);
+    assert!(client.reveal_board(&game, &player2, &fleet(&env), &salt(&env)));
+}
+
+#[test]
+fn test_salvo_size_follows_surviving_ships() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup_salvo(&env, SalvoSize::SurvivingShips);
+    assert_eq!(client.get_salvo_size(&game, &player2), 2);
+
+    fire_salvo(&env, &client, game, &player1, &player2, &[(9, HIT), (14, sinks(2))]).unwrap();
+
+    assert_eq!(client.get_salvo_size(&game, &player2), 1);
+    assert_eq!(
+        fire_salvo(&env, &client, game, &player2, &player1, &[(0, HIT), (1, HIT)]),
+        Err(BattleshipError::InvalidSalvo)
+    );
+    assert_eq!(fire_salvo(&env, &client, game, &player2, &player1, &[(0, HIT)]), Ok(1));
+}
+
+#[test]
+fn test_salvo_claims_are_checked() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup_salvo(&env, SalvoSize::Fixed(2));
+    fire_salvo(&env, &client, game, &player1, &player2, &[(9, HIT), (3, MISS)]).unwrap();
+    fire_salvo(&env, &client, game, &player2, &player1, &[(3, MISS), (4, MISS)]).unwrap();
+
+    for (salvo, error) in [
+        (&[(0, HIT)][..], BattleshipError::InvalidSalvo),
+        (&[(0, HIT), (0, HIT)][..], BattleshipError::CellAlreadyShot),
+        (&[(0, HIT), (9, HIT)][..], BattleshipError::CellAlreadyShot),
+        (&[(0, HIT), (25, MISS)][..], BattleshipError::InvalidShotIndex),
+        (&[(0, HIT), (1, (0, 1))][..], BattleshipError::InvalidShotResult),
+    ] {
+        assert_eq!(fire_salvo(&env, &client, game, &player1, &player2, salvo), Err(error));
+    }
+    assert_eq!(
+        client.try_shoot(&game, &player1, &0, &1, &0, &prove(&env, SHOT_SEED, &vec![&env])),
+        Err(Ok(BattleshipError::WrongGameMode))
+    );
+}
+
+#[test]
+fn test_salvo_needs_a_salvo_game_and_key() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup_committed(&env);
+
+    assert_eq!(
+        client.try_get_salvo_size(&game, &player1),
+        Err(Ok(BattleshipError::WrongGameMode))
+    );
+    assert_eq!(
+        client.try_fire_salvo(&game, &player1, &vec![&env], &vec![&env], &vec![&env], &prove(&env, 0, &vec![&env])),
+        Err(Ok(BattleshipError::WrongGameMode))
+    );
+    assert_eq!(
+        client.try_start_salvo(&player1, &player2, &SalvoSize::Fixed(3), &None),
+        Err(Ok(BattleshipError::InvalidSalvo))
+    );
+    assert_eq!(
+        client.try_set_salvo_key(&6, &test_vk(&env, salvo_seed(6), 20)),
+        Err(Ok(BattleshipError::InvalidSalvo))
+    );
+}
+
+#[test]
+fn test_only_the_admin_sets_salvo_keys() {
+    let env = Env::default();
+    let (client, _, player1, _) = setup(&env);
+    let forged = test_vk(&env, SHOT_SEED, 5);
+
+    // player1 signs, but the key belongs to the admin
+    env.mock_auths(&[MockAuth {
+        address: &player1,
+        invoke: &MockAuthInvoke {
+            contract: &client.address,
+            fn_name: "set_salvo_key",
+            args: (1u32, forged.clone()).into_val(&env),
+            sub_invokes: &[],
+        },
+    }]);
+    assert!(client.try_set_salvo_key(&1, &forged).is_err());
+}
+
+#[test]
+fn test_settle_shots_verifies_the_batch_at_once() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup_committed(&env);
+    shoot(&env, &client, game, &player1, &player2, 0, HIT);
+    let single_cost = env.cost_estimate().budget().cpu_instruction_cost();
+
+    // Player 2's turn, then player 1's, then player 2's again
+    let claims = [(3, MISS), (9, HIT), (0, HIT)];
+    let (shots, proofs) = batch(&env, &client, game, [&player1, &player2], &claims);
+    assert_eq!(client.settle_shots(&game, &player2, &shots, &proofs), 1);
+    // Three proofs for well under the price of two separate checks
+    assert!(env.cost_estimate().budget().cpu_instruction_cost() < 2 * single_cost);
+    // Both players sign a batch that settles the other's turns too
+    let auths = env.auths();
+    assert!([&player1, &player2].iter().all(|player| auths.iter().any(|(signer, _)| signer == *player)));
+
+    shoot(&env, &client, game, &player1, &player2, 14, sinks(2));
+    assert_eq!(client.get_sunk_ships(&game, &player2), vec![&env, 2]);
+    assert_eq!(client.get_shots_at(&game, &player2).len(), 3);
+    assert_eq!(client.get_shots_at(&game, &player1).len(), 2);
+    client.end_game(&game, &player1);
+    assert!(client.reveal_board(&game, &player2, &fleet(&env), &salt(&env)));
+}
+
+#[test]
+fn test_settle_shots_rejects_a_batch_with_one_bad_proof() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup_committed(&env);
+
+    // The last proof answers a different claim than the shot it is settled with
+    let targets = [&player2, &player1];
+    let (mut shots, proofs) = batch(&env, &client, game, targets, &[(9, HIT), (3, MISS), (14, HIT)]);
+    shots.set(2, Shot { index: 14, result: 1, sunk: 2 });
+    assert_eq!(
+        client.try_settle_shots(&game, &player1, &shots, &proofs),
+        Err(Ok(BattleshipError::ProofVerificationFailed))
+    );
+    // Proofs made in the wrong order see the wrong prior hits
+    let (shots, mut proofs) = batch(&env, &client, game, targets, &[(9, HIT), (3, MISS), (14, sinks(2))]);
+    let first = proofs.get(0).unwrap();
+    proofs.set(0, proofs.get(2).unwrap());
+    proofs.set(2, first);
+    assert_eq!(
+        client.try_settle_shots(&game, &player1, &shots, &proofs),
+        Err(Ok(BattleshipError::ProofVerificationFailed))
+    );
+    assert_eq!(client.get_shots_at(&game, &player2), vec![&env]);
+}
+
+#[test]
+fn test_settle_shots_rejects_malformed_batches() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup_committed(&env);
+    let targets = [&player2, &player1];
+    let (shots, mut proofs) = batch(&env, &client, game, targets, &[(9, HIT), (3, MISS)]);
+    proofs.pop_back();
+
+    assert_eq!(
+        client.try_settle_shots(&game, &player1, &shots, &proofs),
+        Err(Ok(BattleshipError::MalformedProof))
+    );
+    assert_eq!(
+        client.try_settle_shots(&game, &player1, &vec![&env], &vec![&env]),
+        Err(Ok(BattleshipError::MalformedProof))
+    );
+    let claims = [(9, HIT), (3, MISS), (4, MISS), (5, MISS), (6, MISS)];
+    let (shots, proofs) = batch(&env, &client, game, targets, &claims);
+    assert_eq!(
+        client.try_settle_shots(&game, &player1, &shots, &proofs),
+        Err(Ok(BattleshipError::BatchTooLarge))
+    );
+
+    let (salvo_client, salvo_game, salvo_player, salvo_target) = setup_salvo(&env, SalvoSize::Fixed(2));
+    let (shots, proofs) = batch(&env, &salvo_client, salvo_game, [&salvo_target, &salvo_player], &[(9, HIT), (3, MISS)]);
+    assert_eq!(
+        salvo_client.try_settle_shots(&salvo_game, &salvo_player, &shots, &proofs),
+        Err(Ok(BattleshipError::WrongGameMode))
+    );
+}
+
+#[test]
+fn test_players_take_turns_shooting() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup_committed(&env);
+    let try_shoot = |shooter: &Address, target: &Address, index: u32| {
+        let proof = prove(&env, SHOT_SEED, &shot_signals(&env, &client, game, target, index, MISS));
+        client.try_shoot(&game, shooter, &index, &0, &0, &proof)
+    };
+    assert_eq!(try_shoot(&player2, &player1, 3), Err(Ok(BattleshipError::NotYourTurn)));
+
+    shoot(&env, &client, game, &player1, &player2, 3, MISS);
+    assert_eq!(try_shoot(&player1, &player2, 4), Err(Ok(BattleshipError::NotYourTurn)));
+
+    // Nor can the player who just fired settle the next turns in a batch
+    let (shots, proofs) = batch(&env, &client, game, [&player2, &player1], &[(4, MISS), (3, MISS)]);
+    assert_eq!(
+        client.try_settle_shots(&game, &player1, &shots, &proofs),
+        Err(Ok(BattleshipError::NotYourTurn))
+    );
+
+    shoot(&env, &client, game, &player2, &player1, 3, MISS);
+    shoot(&env, &client, game, &player1, &player2, 4, MISS);
+    assert_eq!(client.get_shots_at(&game, &player2).len(), 2);
+}
+
+#[test]
+fn test_wager_is_escrowed_and_paid_to_the_winner() {
+    let env = Env::default();
+    let (client, game, player1, player2, token) = setup_wagered(&env, 0);
+    assert_eq!(client.get_wager(&game), Some(Wager { token: token.address.clone(), stake: STAKE }));
+
+    commit_fleet(&env, &client, game, &player1);
+    commit_fleet(&env, &client, game, &player2);
+    assert_eq!(token.balance(&player1), BALANCE - STAKE);
+    assert_eq!(token.balance(&client.address), 2 * STAKE);
+    assert_eq!(client.get_escrow(&game), 2 * STAKE);
+
+    shoot(&env, &client, game, &player1, &player2, 3, MISS);
+    shoot(&env, &client, game, &player2, &player1, 0, HIT);
+    assert_eq!(client.end_game(&game, &player1), Some(player2.clone()));
+
+    assert_eq!(token.balance(&player1), BALANCE - STAKE);
+    assert_eq!(token.balance(&player2), BALANCE + STAKE);
+    assert_eq!(token.balance(&client.address), 0);
+    assert_eq!(client.get_escrow(&game), 0);
+}
+
+#[test]
+fn test_draw_returns_each_stake() {
+    let env = Env::default();
+    let (client, game, player1, player2, token) = setup_wagered(&env, 0);
+    commit_fleet(&env, &client, game, &player1);
+    commit_fleet(&env, &client, game, &player2);
+
+    assert_eq!(client.end_game(&game, &player2), None);
+
+    assert_eq!(client.get_winner(&game), None);
+    assert_eq!(token.balance(&player1), BALANCE);
+    assert_eq!(token.balance(&player2), BALANCE);
+}
+
+#[test]
+fn test_forfeit_hands_the_escrow_to_the_opponent() {
+    let env = Env::default();
+    let (client, game, player1, player2, token) = setup_wagered(&env, 0);
+    commit_fleet(&env, &client, game, &player1);
+    commit_fleet(&env, &client, game, &player2);
+
+    client.forfeit(&game, &player2);
+
+    assert_eq!(client.get_winner(&game), Some(player1.clone()));
+    assert_eq!(token.balance(&player1), BALANCE + STAKE);
+    assert_eq!(
+        client.try_forfeit(&game, &player1),
+        Err(Ok(BattleshipError::GameAlreadyEnded))
+    );
+}
+
+#[test]
+fn test_timeout_runs_against_whoever_owes_the_next_action() {
+    let env = Env::default();
+    let (client, game, player1, player2, token) = setup_wagered(&env, 0);
+    commit_fleet(&env, &client, game, &player1);
+    commit_fleet(&env, &client, game, &player2);
+
+    // Player 1 owes the first shot
+    assert_eq!(
+        client.try_claim_timeout(&game, &player1),
+        Err(Ok(BattleshipError::AwaitingYourMove))
+    );
+    env.ledger().with_mut(|ledger| ledger.sequence_number += 100);
+    client.call_shots(&game, &player1, &vec![&env, 0]);
+
+    // Player 2 now owes the proof, and calling restarted the clock
+    assert_eq!(
+        client.try_claim_timeout(&game, &player2),
+        Err(Ok(BattleshipError::AwaitingYourMove))
+    );
+    assert_eq!(
+        client.try_claim_timeout(&game, &player1),
+        Err(Ok(BattleshipError::TimeoutNotReached))
+    );
+    env.ledger().with_mut(|ledger| ledger.sequence_number += 121);
+
+    client.claim_timeout(&game, &player1);
+
+    assert_eq!(client.get_winner(&game), Some(player1.clone()));
+    assert_eq!(token.balance(&player1), BALANCE + STAKE);
+    assert_eq!(token.balance(&player2), BALANCE - STAKE);
+}
+
+#[test]
+fn test_called_shots_are_answered_by_the_defender() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup_committed(&env);
+    assert_eq!(
+        client.try_call_shots(&game, &player2, &vec![&env, 0]),
+        Err(Ok(BattleshipError::NotYourTurn))
+    );
+    assert_eq!(
+        client.try_call_shots(&game, &player1, &vec![&env, 0, 1]),
+        Err(Ok(BattleshipError::InvalidSalvo))
+    );
+    client.call_shots(&game, &player1, &vec![&env, 0]);
+
+    // The defender cannot fire back before answering
+    let proof = prove(&env, SHOT_SEED, &shot_signals(&env, &client, game, &player1, 0, HIT));
+    assert_eq!(
+        client.try_shoot(&game, &player2, &0, &1, &0, &proof),
+        Err(Ok(BattleshipError::NotYourTurn))
+    );
+
+    let proof = prove(&env, SHOT_SEED, &shot_signals(&env, &client, game, &player2, 0, HIT));
+    let (results, sunk) = (vec![&env, 1], vec![&env, 0]);
+    assert_eq!(client.answer_shots(&game, &player2, &results, &sunk, &proof), 1);
+    assert_eq!(client.get_shots_at(&game, &player2).len(), 1);
+    assert_eq!(
+        client.try_answer_shots(&game, &player2, &results, &sunk, &proof),
+        Err(Ok(BattleshipError::NoCalledShot))
+    );
+    assert_eq!(client.get_game(&game).unwrap().on_clock, Some(player2));
+}
+
+#[test]
+fn test_a_game_waiting_for_a_board_can_be_called_off() {
+    let env = Env::default();
+    let (client, game, player1, player2, token) = setup_wagered(&env, 0);
+    // Player 2 never commits a board
+    commit_fleet(&env, &client, game, &player1);
+    env.ledger().with_mut(|ledger| ledger.sequence_number += 1_000);
+
+    assert_eq!(
+        client.try_claim_timeout(&game, &player1),
+        Err(Ok(BattleshipError::NotAllBoardsCommitted))
+    );
+    assert!(client.cancel(&game, &player1));
+    assert_eq!(client.get_winner(&game), None);
+    assert_eq!(token.balance(&player1), BALANCE);
+    assert_eq!(token.balance(&player2), BALANCE);
+}
+
+#[test]
+fn test_mutual_cancel_refunds_the_deposits() {
+    let env = Env::default();
+    let (client, game, player1, player2, token) = setup_wagered(&env, 0);
+    commit_fleet(&env, &client, game, &player1);
+    commit_fleet(&env, &client, game, &player2);
+
+    assert!(!client.cancel(&game, &player1));
+    assert_eq!(client.get_game_state(&game), (true, false));
+    assert!(client.cancel(&game, &player2));
+
+    assert_eq!(client.get_game_state(&game), (true, true));
+    assert_eq!(client.get_winner(&game), None);
+    assert_eq!(token.balance(&player1), BALANCE);
+    assert_eq!(token.balance(&player2), BALANCE);
+    assert_eq!(token.balance(&client.address), 0);
+}
+
+#[test]
+fn test_wager_needs_a_positive_stake() {
+    let env = Env::default();
+    let (client, _, player1, player2) = setup(&env);
+    let token = env.register_stellar_asset_contract_v2(Address::generate(&env)).address();
+
+    for stake in [0, -1] {
+        assert_eq!(
+            client.try_start_game(&player1, &player2, &Some(Wager { token: token.clone(), stake })),
+            Err(Ok(BattleshipError::InvalidWager))
+        );
+    }
+}
+
+#[test]
+fn test_fee_is_taken_from_the_pot_and_withdrawn_to_the_treasury() {
+    let env = Env::default();
+    let (client, game, player1, player2, token) = setup_wagered(&env, 500);
+    commit_fleet(&env, &client, game, &player1);
+    commit_fleet(&env, &client, game, &player2);
+
+    client.forfeit(&game, &player1);
+
+    // 5% of the 200 pot
+    let fee_event = (
+        client.address.clone(),
+        (symbol_short!("fee"), symbol_short!("collect")).into_val(&env),
+        (game, token.address.clone(), 10i128).into_val(&env),
+    );
+    assert!(env.events().all().contains(&fee_event));
+    assert_eq!(token.balance(&player2), BALANCE + STAKE - 10);
+    assert_eq!(client.get_fees(&token.address), 10);
+
+    let treasury = Address::generate(&env);
+    client.set_treasury(&treasury);
+    assert_eq!(client.withdraw_fees(&token.address), 10);
+    assert_eq!(token.balance(&treasury), 10);
+    assert_eq!(token.balance(&client.address), 0);
+    assert_eq!(client.get_fees(&token.address), 0);
+    assert_eq!(client.withdraw_fees(&token.address), 0);
+}
+
+#[test]
+fn test_fee_applies_to_draws_but_not_to_cancels() {
+    let env = Env::default();
+    let (client, game, player1, player2, token) = setup_wagered(&env, 500);
+    commit_fleet(&env, &client, game, &player1);
+    commit_fleet(&env, &client, game, &player2);
+    client.end_game(&game, &player1);
+    assert_eq!(token.balance(&player1), BALANCE - 5);
+    assert_eq!(token.balance(&player2), BALANCE - 5);
+
+    let wager = client.get_wager(&game);
+    let cancelled = client.start_game(&player1, &player2, &wager);
+    client.accept_game(&cancelled, &player2);
+    commit_fleet(&env, &client, cancelled, &player1);
+    assert!(client.cancel(&cancelled, &player1));
+    assert_eq!(token.balance(&player1), BALANCE - 5);
+    assert_eq!(client.get_fees(&token.address), 10);
+}
+
+#[test]
+fn test_fee_is_capped_and_fixed_when_a_game_starts() {
+    let env = Env::default();
+    let (client, game, player1, player2, token) = setup_wagered(&env, 1_000);
+    assert_eq!(client.try_set_fee(&1_001), Err(Ok(BattleshipError::FeeTooHigh)));
+    commit_fleet(&env, &client, game, &player1);
+    commit_fleet(&env, &client, game, &player2);
+
+    client.set_fee(&0);
+    client.forfeit(&game, &player2);
+
+    assert_eq!(client.get_fee_bps(), 0);
+    assert_eq!(client.get_fees(&token.address), 20);
+    assert_eq!(token.balance(&player1), BALANCE + STAKE - 20);
+}
+
+// Every way a staked game pays out, each charged at most fee_bps of the pot
+#[test]
+fn test_fees_never_exceed_the_pot_share() {
+    let fee_bps = 1_000;
+    for ending in 0..5 {
+        let env = Env::default();
+        let (client, game, player1, player2, token) = setup_wagered(&env, fee_bps);
+        commit_fleet(&env, &client, game, &player1);
+        commit_fleet(&env, &client, game, &player2);
+        match ending {
+            0 => {
+                shoot(&env, &client, game, &player1, &player2, 3, MISS);
+                shoot(&env, &client, game, &player2, &player1, 0, HIT);
+                client.end_game(&game, &player1);
+            }
+            1 => {
+                client.end_game(&game, &player1);
+            }
+            2 => client.forfeit(&game, &player2),
+            3 => {
+                env.ledger().with_mut(|ledger| ledger.sequence_number += 121);
+                client.claim_timeout(&game, &player2);
+            }
+            _ => {
+                client.cancel(&game, &player1);
+                client.cancel(&game, &player2);
+            }
+        }
+
+        let fees = client.get_fees(&token.address);
+        assert!(fees <= 2 * STAKE * fee_bps as i128 / 10_000);
+        assert_eq!(client.get_escrow(&game), 0);
+        assert_eq!(token.balance(&player1) + token.balance(&player2) + fees, 2 * BALANCE);
+    }
+}
+
+#[test]
+fn test_init_runs_once() {
+    let env = Env::default();
+    let (client, _, player1, _) = setup(&env);
+    let (shot_vk, placement_vk) = (test_vk(&env, SHOT_SEED, 5), test_vk(&env, PLACEMENT_SEED, 6));
+
+    assert_eq!(
+        client.try_init(&player1, &shot_vk, &placement_vk, &fleet_lengths(&env)),
+        Err(Ok(BattleshipError::AlreadyInitialized))
+    );
+    assert_ne!(client.get_treasury(), Some(player1));
+}
+
+#[test]
+fn test_sessions_are_reported_to_the_game_hub() {
+    let env = Env::default();
+    let (client, wagered, player1, player2, token) = setup_wagered(&env, 0);
+    let hub = MockGameHubClient::new(&env, &env.register(MockGameHub, ()));
+    client.set_game_hub(&hub.address);
+
+    let wager = client.get_wager(&wagered);
+    let game = client.start_game(&player1, &player2, &wager);
+    client.accept_game(&game, &player2);
+    let session_id = client.get_hub_session(&game).unwrap();
+    let session = hub.get_session(&session_id).unwrap();
+    assert_eq!(session.game_id, client.address);
+    assert_eq!((session.player1, session.player2), (player1.clone(), player2.clone()));
+    assert_eq!((session.player1_points, session.player2_points), (STAKE, STAKE));
+
+    commit_fleet(&env, &client, game, &player1);
+    commit_fleet(&env, &client, game, &player2);
+    client.forfeit(&game, &player1);
+    assert_eq!(hub.get_session(&session_id).unwrap().player1_won, Some(false));
+    assert_eq!(token.balance(&player2), BALANCE + STAKE);
+}
+
+// Starts a game on a Game Hub where another game already holds session `taken`
+fn start_with_taken_session(env: &Env, taken: u32) -> (ZkBattleshipClient<'_>, GameHubClient<'_>, u32) {
+    let (client, _, player1, player2) = setup(env);
+    let hub = GameHubClient::new(env, &env.register(GameHub, ()));
+    hub.init(&Address::generate(env));
+    hub.register_game(&client.address, &symbol_short!("battle"));
+    let other = Address::generate(env);
+    hub.register_game(&other, &symbol_short!("other"));
+    hub.start_game(&other, &taken, &Address::generate(env), &Address::generate(env), &0, &0);
+    client.set_game_hub(&hub.address);
+
+    let game = client.start_game(&player1, &player2, &None);
+    client.accept_game(&game, &player2);
+    (client, hub, game)
+}
+
+#[test]
+fn test_a_taken_hub_session_id_is_drawn_again() {
+    // The same calls on a fresh Env draw the same ids, so take the first one
+    let first_draw = {
+        let env = Env::default();
+        let (client, _, game) = start_with_taken_session(&env, 0);
+        client.get_hub_session(&game).unwrap()
+    };
+    let env = Env::default();
+    let (client, hub, game) = start_with_taken_session(&env, first_draw);
+
+    let session_id = client.get_hub_session(&game).unwrap();
+    assert_ne!(session_id, first_draw);
+    assert_eq!(hub.get_session(&session_id).unwrap().game, client.address);
+    let failed = env.events().all().iter().any(|(_, topics, _)| {
+        topics == (symbol_short!("hub"), symbol_short!("failed")).into_val(&env)
+    });
+    assert!(!failed);
+}
+
+#[test]
+fn test_a_failing_game_hub_does_not_block_the_game() {
+    let env = Env::default();
+    let (client, _, player1, player2) = setup(&env);
+    let hub = MockGameHubClient::new(&env, &env.register(MockGameHub, ()));
+    client.set_game_hub(&hub.address);
+    hub.set_failing(&true);
+
+    let game = client.start_game(&player1, &player2, &None);
+    client.accept_game(&game, &player2);
+    let failed_event = (
+        client.address.clone(),
+        (symbol_short!("hub"), symbol_short!("failed")).into_val(&env),
+        (game, symbol_short!("start")).into_val(&env),
+    );
+    assert!(env.events().all().contains(&failed_event));
+    // The hub never took the session, so the game keeps none and reports no result
+    assert_eq!(client.get_hub_session(&game), None);
+
+    client.forfeit(&game, &player2);
+    assert_eq!(client.get_winner(&game), Some(player1));
+    let end_failed = (
+        client.address.clone(),
+        (symbol_short!("hub"), symbol_short!("failed")).into_val(&env),
+        (game, symbol_short!("end")).into_val(&env),
+    );
+    assert!(!env.events().all().contains(&end_failed));
+}
+
+#[test]
+fn test_accepting_a_challenge_starts_the_game() {
+    let env = Env::default();
+    let (client, wagered, player1, player2, token) = setup_wagered(&env, 0);
+    let wager = client.get_wager(&wagered);
+
+    let id = client.create_challenge(&player1, &GameMode::Classic, &wager, &None, &None);
+    let game = client.accept_challenge(&id, &player2);
+
+    // Both players sign the transaction that starts the game
+    let auths = env.auths();
+    assert!([&player1, &player2].iter().all(|player| auths.iter().any(|(signer, _)| signer == *player)));
+    assert_eq!(client.get_wager(&game), wager);
+    assert_eq!(client.get_game_state(&game), (true, false));
+    assert_eq!(client.get_challenge(&id), None);
+    assert_eq!(client.list_open_challenges(&0, &10), vec![&env]);
+
+    commit_fleet(&env, &client, game, &player1);
+    commit_fleet(&env, &client, game, &player2);
+    client.forfeit(&game, &player2);
+    assert_eq!(token.balance(&player1), BALANCE + STAKE);
+}
+
+#[test]
+fn test_direct_challenges_are_for_the_named_opponent() {
+    let env = Env::default();
+    let (client, _, player1, player2) = setup(&env);
+    let stranger = Address::generate(&env);
+
+    let id = client.create_challenge(&player1, &GameMode::Classic, &None, &Some(player2.clone()), &None);
+    assert_eq!(client.try_accept_challenge(&id, &stranger), Err(Ok(BattleshipError::InvalidPlayer)));
+    assert_eq!(client.try_accept_challenge(&id, &player1), Err(Ok(BattleshipError::InvalidPlayer)));
+    assert_eq!(
+        client.try_create_challenge(&player1, &GameMode::Classic, &None, &Some(player1.clone()), &None),
+        Err(Ok(BattleshipError::InvalidPlayer))
+    );
+
+    client.cancel_challenge(&id);
+    assert_eq!(client.try_accept_challenge(&id, &player2), Err(Ok(BattleshipError::ChallengeNotFound)));
+    assert_eq!(client.try_cancel_challenge(&id), Err(Ok(BattleshipError::ChallengeNotFound)));
+}
+
+#[test]
+fn test_open_challenges_are_listed_in_pages() {
+    let env = Env::default();
+    let (client, _, player1, player2) = setup(&env);
+    let mut ids = Vec::new(&env);
+    for _ in 0..3 {
+        ids.push_back(client.create_challenge(&player1, &GameMode::Classic, &None, &None, &None));
+    }
+    client.cancel_challenge(&ids.get(0).unwrap());
+
+    // The last challenge moved into the cancelled one's slot
+    let page = client.list_open_challenges(&0, &1);
+    assert_eq!(page.len(), 1);
+    assert_eq!((page.get(0).unwrap().token, page.get(0).unwrap().stake), (None, 0));
+    assert_eq!(page.get(0).unwrap().id, ids.get(2).unwrap());
+    assert_eq!(page.get(0).unwrap().slot, 0);
+    assert_eq!(page.get(0).unwrap().creator, player1);
+    assert_eq!(client.list_open_challenges(&1, &5).get(0).unwrap().id, ids.get(1).unwrap());
+    assert_eq!(client.list_open_challenges(&2, &5), vec![&env]);
+    assert_eq!(client.list_open_challenges(&u32::MAX, &u32::MAX), vec![&env]);
+
+    let salvo = client.create_challenge(&player2, &GameMode::Salvo(SalvoSize::Fixed(2)), &None, &None, &None);
+    let game = client.accept_challenge(&salvo, &player1);
+    assert_eq!(client.get_salvo_size(&game, &player1), 2);
+    assert_eq!(
+        client.try_create_challenge(&player2, &GameMode::Salvo(SalvoSize::Fixed(4)), &None, &None, &None),
+        Err(Ok(BattleshipError::InvalidSalvo))
+    );
+}
+
+#[test]
+fn test_open_challenges_are_capped_per_creator() {
+    let env = Env::default();
+    let (client, _, player1, player2) = setup(&env);
+    let mut ids = Vec::new(&env);
+    for _ in 0..5 {
+        ids.push_back(client.create_challenge(&player1, &GameMode::Classic, &None, &None, &None));
+    }
+    assert_eq!(
+        client.try_create_challenge(&player1, &GameMode::Classic, &None, &None, &None),
+        Err(Ok(BattleshipError::TooManyChallenges))
+    );
+    // Other creators are not held back
+    client.create_challenge(&player2, &GameMode::Classic, &None, &None, &None);
+
+    // A taken challenge frees a place
+    client.accept_challenge(&ids.get(0).unwrap(), &player2);
+    let id = client.create_challenge(&player1, &GameMode::Classic, &None, &None, &None);
+    assert_eq!(client.list_open_challenges(&0, &20).len(), 6);
+    assert_eq!(client.get_challenge(&id).unwrap().slot, 5);
+}
+
+#[test]
+fn test_rating_band_matches_players_by_hub_rating() {
+    let env = Env::default();
+    let (client, _, player1, player2) = setup(&env);
+    let player3 = Address::generate(&env);
+    let banded = client.create_challenge(&player1, &GameMode::Classic, &None, &None, &Some(30));
+    assert_eq!(
+        client.try_accept_challenge(&banded, &player3),
+        Err(Ok(BattleshipError::RatingUnavailable))
+    );
+
+    let hub = GameHubClient::new(&env, &env.register(GameHub, ()));
+    hub.init(&Address::generate(&env));
+    hub.register_game(&client.address, &symbol_short!("battle"));
+    client.set_game_hub(&hub.address);
+    // Player 1 beats player 2: 1520 against 1480
+    let game = client.start_game(&player1, &player2, &None);
+    client.accept_game(&game, &player2);
+    client.forfeit(&game, &player2);
+
+    assert_eq!(
+        client.try_accept_challenge(&banded, &player2),
+        Err(Ok(BattleshipError::OutsideRatingBand))
+    );
+    // Unrated player 3 is 20 points away
+    let game = client.accept_challenge(&banded, &player3);
+    assert_eq!(client.get_hub_session(&game).map(|id| hub.get_session(&id).is_some()), Some(true));
+}
+
+#[test]
+fn test_game_waits_for_the_invited_player() {
+    let env = Env::default();
+    let (client, _, player1, player2) = setup(&env);
+    let stranger = Address::generate(&env);
+
+    let game = client.start_game(&player1, &player2, &None);
+    assert_eq!(client.get_game_state(&game), (false, false));
+    assert_eq!(client.get_invite_deadline(&game), Some(env.ledger().sequence() + 17_280));
+    let hash = board_hash(&env, 1);
+    assert_eq!(
+        client.try_commit_board(&game, &player1, &hash, &placement_proof(&env, &hash, &FLEET)),
+        Err(Ok(BattleshipError::GameNotStarted))
+    );
+    assert_eq!(client.try_accept_game(&game, &stranger), Err(Ok(BattleshipError::InvalidPlayer)));
+    assert_eq!(client.try_accept_game(&game, &player1), Err(Ok(BattleshipError::InvalidPlayer)));
+
+    client.accept_game(&game, &player2);
+
+    assert_eq!(client.get_game_state(&game), (true, false));
+    assert_eq!(client.get_invite_deadline(&game), None);
+    assert_eq!(client.try_accept_game(&game, &player2), Err(Ok(BattleshipError::GameAlreadyStarted)));
+    commit_fleet(&env, &client, game, &player1);
+}
+
+#[test]
+fn test_invites_expire_and_can_be_withdrawn() {
+    let env = Env::default();
+    let (client, _, player1, player2) = setup(&env);
+    let game = client.start_game(&player1, &player2, &None);
+    let declined = client.start_game(&player1, &player2, &None);
+
+    env.ledger().with_mut(|ledger| ledger.sequence_number += 17_281);
+    assert_eq!(client.try_accept_game(&game, &player2), Err(Ok(BattleshipError::InviteExpired)));
+
+    // Either side can drop a pending invite on their own
+    assert!(client.cancel(&game, &player1));
+    assert!(client.cancel(&declined, &player2));
+    assert_eq!(client.get_game_state(&game), (false, true));
+    assert_eq!(client.try_accept_game(&game, &player2), Err(Ok(BattleshipError::GameAlreadyEnded)));
+}
+
+// What the contract extends its storage to: 30 days of 5-second ledgers
+const DAY: u32 = 17_280;
+const TTL_BUMP: u32 = 30 * DAY;
+
+fn ttl(env: &Env, client: &ZkBattleshipClient, key: &DataKey) -> u32 {
+    env.as_contract(&client.address, || env.storage().persistent().get_ttl(key))
+}
+
+#[test]
+fn test_moves_keep_the_game_and_keys_alive() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup_committed(&env);
+    assert_eq!(ttl(&env, &client, &DataKey::Game(game)), TTL_BUMP);
+
+    // Each gap is far past the 4096-ledger default a fresh entry gets
+    for (shooter, target) in [(&player1, &player2), (&player2, &player1)] {
+        env.ledger().with_mut(|ledger| ledger.sequence_number += 20 * DAY);
+        shoot(&env, &client, game, shooter, target, 0, (1, 0));
+    }
+
+    for key in [DataKey::Game(game), DataKey::ShotsAt(game, player2.clone())] {
+        assert_eq!(ttl(&env, &client, &key), TTL_BUMP);
+    }
+    let instance_ttl = env.as_contract(&client.address, || env.storage().instance().get_ttl());
+    assert_eq!(instance_ttl, TTL_BUMP);
+    assert_eq!(client.get_hits(&game, &player2), 1);
+}
+
+#[test]
+fn test_admin_bump_ttl_keeps_an_idle_game_alive() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup_committed(&env);
+    let challenge = client.create_challenge(&player1, &GameMode::Classic, &None, &None, &None);
+
+    env.ledger().with_mut(|ledger| ledger.sequence_number += 25 * DAY);
+    client.bump_ttl(&vec![&env, game], &vec![&env, challenge]);
+    assert_eq!(ttl(&env, &client, &DataKey::Game(game)), TTL_BUMP);
+    assert_eq!(ttl(&env, &client, &DataKey::Challenge(challenge)), TTL_BUMP);
+    assert_eq!(ttl(&env, &client, &DataKey::OpenSlot(0)), TTL_BUMP);
+    assert_eq!(client.try_bump_ttl(&vec![&env, 99], &vec![&env]), Err(Ok(BattleshipError::GameNotStarted)));
+    assert_eq!(
+        client.try_bump_ttl(&vec![&env], &vec![&env, 99]),
+        Err(Ok(BattleshipError::ChallengeNotFound))
+    );
+
+    // Past the first 30 days, which the game alone would not have outlived
+    env.ledger().with_mut(|ledger| ledger.sequence_number += 25 * DAY);
+    shoot(&env, &client, game, &player1, &player2, 0, (1, 0));
+    assert_eq!(client.get_hits(&game, &player1), 1);
+}
+
+#[test]
+fn test_unanswered_cancel_requests_lapse() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup_committed(&env);
+
+    assert!(!client.cancel(&game, &player1));
+    env.ledger().with_mut(|ledger| ledger.sequence_number += DAY + 1);
+    assert!(!client.cancel(&game, &player2));
+    assert_eq!(client.get_game_state(&game), (true, false));
+    assert!(client.cancel(&game, &player1));
+}
+
+// Ledger entries (read, written) by one call, measured with this test on the
+// layout before each game was packed into a single GameState entry
+const UNPACKED_START: (u32, u32) = (20, 10);
+const UNPACKED_SHOOT: (u32, u32) = (25, 5);
+const UNPACKED_END: (u32, u32) = (27, 3);
+
+// Checks the last call read and wrote at most `limit` ledger entries, and
+// strictly fewer of each than `unpacked`
+fn assert_entries(env: &Env, limit: (u32, u32), unpacked: (u32, u32)) {
+    let resources = env.cost_estimate().resources();
+    let used = (resources.read_entries, resources.write_entries);
+    assert!(used.0 <= limit.0 && used.1 <= limit.1, "{:?} over {:?}", used, limit);
+    assert!(used.0 < unpacked.0 && used.1 < unpacked.1, "{:?} not under {:?}", used, unpacked);
+}
+
+#[test]
+fn test_calls_load_and_store_the_game_once() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup_committed(&env);
+
+    // Beyond the game itself: the target's shot log and the contract instance
+    shoot(&env, &client, game, &player1, &player2, 0, (1, 0));
+    assert_entries(&env, (4, 3), UNPACKED_SHOOT);
+
+    client.start_game(&player1, &player2, &None);
+    assert_entries(&env, (4, 3), UNPACKED_START);
+
+    client.end_game(&game, &player1);
+    // Both players sign, so each of their auth nonces is written too. The old
+    // layout was measured with one signer, so it is charged the second nonce.
+    assert_entries(&env, (8, 3), (UNPACKED_END.0 + 1, UNPACKED_END.1 + 1));
+}
@@ -0,0 +1,269 @@
+// Typed ZK TCG events
+//
+// Every event is published with topics `(name, EVENT_VERSION, game_id)` and one of
+// the payload structs below as data, so an indexer can replay a match from events alone.
+
+use soroban_sdk::{contracttype, symbol_short, Address, BytesN, Env, IntoVal, Symbol, Val};
+
+use crate::{Creature, Target, TcgRules, Trap};
+
+// Bump whenever a payload below changes shape
+pub const EVENT_VERSION: u32 = 4;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum DamageSource {
+    Creature(Address, u32),
+    Spell(u32),
+    Trap(u32),
+    Fatigue,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum GameOverReason {
+    HpDepleted,
+    Conceded,
+    Timeout,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct GameStartedEvent {
+    pub player1: Address,
+    pub player2: Address,
+    pub rules: TcgRules,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct DeckCommittedEvent {
+    pub player: Address,
+    pub hash: BytesN<32>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct CardDrawnEvent {
+    pub player: Address,
+    pub draw_index: u32,
+    pub card_value: u32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct CardPlayedEvent {
+    pub player: Address,
+    pub card_id: u32,
+    pub target: Target,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct CreatureSummonedEvent {
+    pub owner: Address,
+    pub id: u32,
+    pub creature: Creature,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct DamageDealtEvent {
+    pub source: DamageSource,
+    pub target: Target,
+    pub amount: u32,
+    pub remaining: u32, // HP or creature health left after the hit
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct HealedEvent {
+    pub target: Target,
+    pub amount: u32,
+    pub remaining: u32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct BuffedEvent {
+    pub target: Target,
+    pub attack: u32,
+    pub health: u32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct DrawsGrantedEvent {
+    pub player: Address,
+    pub count: u32,
+}
+
+// Draws a player left untaken when their turn ended; `draw_index` is where their deck now stands
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct CardsBurnedEvent {
+    pub player: Address,
+    pub count: u32,
+    pub draw_index: u32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct CreatureDiedEvent {
+    pub owner: Address,
+    pub id: u32,
+    pub card_id: u32,
+}
+
+// `graveyard_size` is the owner's graveyard length after the change
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct GraveyardEvent {
+    pub owner: Address,
+    pub card_id: u32,
+    pub graveyard_size: u32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct TrapSetEvent {
+    pub owner: Address,
+    pub trap_id: u32,
+    pub trap: Trap,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct TrapRevealedEvent {
+    pub owner: Address,
+    pub trap_id: u32,
+    pub card_id: u32,
+    pub trigger: Target, // The attacking creature the trap answers
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct TrapExpiredEvent {
+    pub owner: Address,
+    pub trap_id: u32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct TurnEndedEvent {
+    pub player: Address,
+    pub next: Address,
+    pub turn_number: u32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct GameOverEvent {
+    pub winner: Address,
+    pub reason: GameOverReason,
+}
+
+// A Game Hub call that failed; the game carries on without it
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct HubCallFailedEvent {
+    pub call: Symbol,
+}
+
+fn publish<D: IntoVal<Env, Val>>(env: &Env, name: Symbol, game_id: u32, data: D) {
+    env.events().publish((name, EVENT_VERSION, game_id), data);
+}
+
+pub fn game_started(env: &Env, game_id: u32, player1: &Address, player2: &Address, rules: &TcgRules) {
+    let data = GameStartedEvent { player1: player1.clone(), player2: player2.clone(), rules: rules.clone() };
+    publish(env, symbol_short!("started"), game_id, data);
+}
+
+pub fn deck_committed(env: &Env, game_id: u32, player: &Address, hash: &BytesN<32>) {
+    let data = DeckCommittedEvent { player: player.clone(), hash: hash.clone() };
+    publish(env, symbol_short!("deck"), game_id, data);
+}
+
+pub fn card_drawn(env: &Env, game_id: u32, player: &Address, draw_index: u32, card_value: u32) {
+    let data = CardDrawnEvent { player: player.clone(), draw_index, card_value };
+    publish(env, symbol_short!("draw"), game_id, data);
+}
+
+pub fn card_played(env: &Env, game_id: u32, player: &Address, card_id: u32, target: &Target) {
+    let data = CardPlayedEvent { player: player.clone(), card_id, target: target.clone() };
+    publish(env, symbol_short!("played"), game_id, data);
+}
+
+pub fn creature_summoned(env: &Env, game_id: u32, owner: &Address, id: u32, creature: &Creature) {
+    let data = CreatureSummonedEvent { owner: owner.clone(), id, creature: creature.clone() };
+    publish(env, symbol_short!("summoned"), game_id, data);
+}
+
+pub fn damage_dealt(env: &Env, game_id: u32, source: &DamageSource, target: Target, amount: u32, remaining: u32) {
+    let data = DamageDealtEvent { source: source.clone(), target, amount, remaining };
+    publish(env, symbol_short!("damage"), game_id, data);
+}
+
+pub fn healed(env: &Env, game_id: u32, target: &Target, amount: u32, remaining: u32) {
+    let data = HealedEvent { target: target.clone(), amount, remaining };
+    publish(env, symbol_short!("healed"), game_id, data);
+}
+
+pub fn buffed(env: &Env, game_id: u32, target: &Target, attack: u32, health: u32) {
+    let data = BuffedEvent { target: target.clone(), attack, health };
+    publish(env, symbol_short!("buffed"), game_id, data);
+}
+
+pub fn draws_granted(env: &Env, game_id: u32, player: &Address, count: u32) {
+    let data = DrawsGrantedEvent { player: player.clone(), count };
+    publish(env, symbol_short!("drawfx"), game_id, data);
+}
+
+pub fn cards_burned(env: &Env, game_id: u32, player: &Address, count: u32, draw_index: u32) {
+    let data = CardsBurnedEvent { player: player.clone(), count, draw_index };
+    publish(env, symbol_short!("burned"), game_id, data);
+}
+
+pub fn creature_died(env: &Env, game_id: u32, owner: &Address, id: u32, card_id: u32) {
+    let data = CreatureDiedEvent { owner: owner.clone(), id, card_id };
+    publish(env, symbol_short!("died"), game_id, data);
+}
+
+pub fn card_buried(env: &Env, game_id: u32, owner: &Address, card_id: u32, graveyard_size: u32) {
+    let data = GraveyardEvent { owner: owner.clone(), card_id, graveyard_size };
+    publish(env, symbol_short!("buried"), game_id, data);
+}
+
+pub fn card_exhumed(env: &Env, game_id: u32, owner: &Address, card_id: u32, graveyard_size: u32) {
+    let data = GraveyardEvent { owner: owner.clone(), card_id, graveyard_size };
+    publish(env, symbol_short!("exhumed"), game_id, data);
+}
+
+pub fn trap_set(env: &Env, game_id: u32, owner: &Address, trap_id: u32, trap: &Trap) {
+    let data = TrapSetEvent { owner: owner.clone(), trap_id, trap: trap.clone() };
+    publish(env, symbol_short!("trap_set"), game_id, data);
+}
+
+pub fn trap_revealed(env: &Env, game_id: u32, owner: &Address, trap_id: u32, card_id: u32, trigger: &Target) {
+    let data = TrapRevealedEvent { owner: owner.clone(), trap_id, card_id, trigger: trigger.clone() };
+    publish(env, symbol_short!("sprung"), game_id, data);
+}
+
+pub fn trap_expired(env: &Env, game_id: u32, owner: &Address, trap_id: u32) {
+    let data = TrapExpiredEvent { owner: owner.clone(), trap_id };
+    publish(env, symbol_short!("trap_gone"), game_id, data);
+}
+
+pub fn turn_ended(env: &Env, game_id: u32, player: &Address, next: &Address, turn_number: u32) {
+    let data = TurnEndedEvent { player: player.clone(), next: next.clone(), turn_number };
+    publish(env, symbol_short!("turn_end"), game_id, data);
+}
+
+pub fn game_over(env: &Env, game_id: u32, winner: &Address, reason: GameOverReason) {
+    let data = GameOverEvent { winner: winner.clone(), reason };
+    publish(env, symbol_short!("game_over"), game_id, data);
+}
+
+pub fn hub_call_failed(env: &Env, game_id: u32, call: Symbol) {
+    publish(env, symbol_short!("hub_fail"), game_id, HubCallFailedEvent { call });
+}
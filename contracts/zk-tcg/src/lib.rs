@@ -1,10 +1,15 @@
 #![no_std]
 
 use soroban_sdk::{
-    contract, contracterror, contractimpl, contracttype,
+    contract, contractclient, contracterror, contractimpl, contracttype,
     crypto::bls12_381::{Fr, G1Affine, G2Affine},
-    log, symbol_short, vec, Address, Bytes, BytesN, Env, IntoVal, Symbol, Vec,
+    log, symbol_short, vec, Address, Bytes, BytesN, Env, Map, Vec,
 };
+use zk_hub_interface::HubError;
+
+mod events;
+
+pub use events::{DamageSource, GameOverReason};
 
 #[contracterror]
 #[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
@@ -16,28 +21,233 @@ pub enum TcgError {
     NotYourTurn = 4,
     InvalidProof = 5,
     GameEnded = 6,
+    AlreadyInitialized = 7,
+    Unauthorized = 8,
+    CardNotFound = 9,
+    InvalidCard = 10,
+    InvalidTarget = 11,
+    InvalidRules = 12,
+    BoardFull = 13,
+    HandFull = 14,
+    EmptyHand = 15,
+    GameNotFound = 16,
+    NotEnoughMana = 17,
+    CreatureNotReady = 18,
+    DecksNotCommitted = 19,
+    TimeoutNotReached = 20,
+    GraveyardEmpty = 21,
+    TrapNotFound = 22,
+    TrapSlotsFull = 23,
+    TrapNotTriggered = 24,
+    TrapExpired = 25,
+    CommitmentMismatch = 26,
+    TooManyGames = 27,
+    NoDrawsOwed = 28,
 }
 
 #[contracttype]
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum DataKey {
-    Player1,
-    Player2,
-    DeckHash(Address),
-    Hp(Address),
-    Turn,
-    DrawIndex(Address),
-    Board(Address), 
-    GameStarted,
-    Winner,
+    GameCounter,
+    ActiveGames(Address),
+    Player1(u32),
+    Player2(u32),
+    DeckHash(u32, Address),
+    Hp(u32, Address),
+    Turn(u32),
+    TurnNumber(u32),
+    TurnDeadline(u32),
+    DrawIndex(u32, Address),
+    Board(u32, Address),
+    CreatureCounter(u32),
+    Graveyard(u32, Address),
+    TrapCounter(u32),
+    Traps(u32, Address),
+    TrapTrigger(u32, Address),
+    Fatigue(u32, Address),
+    HandSize(u32, Address),
+    PendingDraws(u32, Address),
+    Mana(u32, Address),
+    MaxMana(u32, Address),
+    Rules(u32),
+    GameStarted(u32),
+    Winner(u32),
+    Admin,
+    Card(u32),
     VerificationKey,
+    GameHub,
+    HubSession(u32),
+}
+
+// Creatures can't attack the turn they arrive, and attack at most once per turn
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum CreatureStatus {
+    Sleeping,
+    Ready,
+    Exhausted,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[contracttype]
 pub struct Creature {
+    pub card_id: u32,
+    pub attack: u32,
+    pub health: u32,
+    pub status: CreatureStatus,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum GamePhase {
+    AwaitingDecks,
+    Active,
+    Ended,
+}
+
+// Per-game format, fixed at `init_game`
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct TcgRules {
+    pub starting_hp: u32,
+    pub deck_size: u32,    // Must match the deck circuit the players prove draws with
+    pub board_slots: u32,
+    pub hand_limit: u32,
+    pub fatigue_step: u32, // Extra damage per empty draw; 0 disables fatigue
+    pub max_mana: u32,     // Mana grows by one each turn up to this cap
+    pub turn_timeout: u32, // Ledgers a player has to act before the opponent can claim the win; u32::MAX never times out
+    pub trap_slots: u32,   // Face-down traps a player may have set at once; 0 disables traps
+    pub trap_duration: u32, // Turns a trap stays armed before it expires unrevealed; u32::MAX never expires
+    pub opening_hand: u32, // Cards each player is owed before their first turn
+}
+
+impl TcgRules {
+    pub fn standard() -> Self {
+        TcgRules {
+            starting_hp: 15,
+            deck_size: 12,
+            board_slots: 3,
+            hand_limit: 7,
+            fatigue_step: 1,
+            max_mana: 10,
+            turn_timeout: 120,
+            trap_slots: 2,
+            trap_duration: 4,
+            opening_hand: 5,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum CardKind {
+    Creature,
+    Spell,
+    Trap,
+}
+
+// What a spell does; values are amounts, `Summon` names a creature card id.
+// `Resurrect` returns the most recently dead creature from the target player's graveyard
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum Effect {
+    DealDamage(u32),
+    Heal(u32),
+    Buff(u32, u32),
+    DrawCards(u32),
+    Destroy,
+    Summon(u32),
+    Resurrect,
+}
+
+// Which targets an effect accepts, relative to the caster
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum TargetRule {
+    Caster,
+    Opponent,
+    AnyPlayer,
+    AnyEnemy,
+    AnyCreature,
+    FriendlyCreature,
+    EnemyCreature,
+    AllEnemyCreatures,
+}
+
+// A concrete target: chosen by the caster, or resolved from a `TargetRule`.
+// Creatures are named by owner and creature id, which stays fixed while the creature lives
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub enum Target {
+    None,
+    Player(Address),
+    Creature(Address, u32),
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct SpellEffect {
+    pub effect: Effect,
+    pub target: TargetRule,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Card {
+    pub kind: CardKind,
+    pub cost: u32,
     pub attack: u32,
     pub health: u32,
+    pub effects: Vec<SpellEffect>,
+}
+
+// A face-down card: only the commitment is public until the owner reveals it
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct Trap {
+    pub commitment: BytesN<32>, // sha256(card_id as 4 big-endian bytes || salt)
+    pub set_turn: u32,
+    pub expires_turn: u32,
+}
+
+// The last attack made against a player, which their traps may answer
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct TrapTrigger {
+    pub attacker: Address,
+    pub creature_id: u32,
+    pub turn_number: u32,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct TcgPlayerView {
+    pub address: Address,
+    pub hp: u32,
+    pub board: Map<u32, Creature>, // Keyed by creature id
+    pub graveyard: Vec<u32>,       // Card ids, oldest first
+    pub traps: Map<u32, Trap>,     // Keyed by trap id
+    pub hand_size: u32,
+    pub draw_index: u32,
+    pub pending_draws: u32, // Draws granted but not yet taken through `draw_card`
+    pub fatigue: u32,
+    pub mana: u32,
+    pub max_mana: u32,
+    pub deck_committed: bool,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[contracttype]
+pub struct TcgGameView {
+    pub game_id: u32,
+    pub rules: TcgRules,
+    pub player1: TcgPlayerView,
+    pub player2: TcgPlayerView,
+    pub turn: Address,
+    pub turn_number: u32,
+    pub turn_deadline: u32,
+    pub phase: GamePhase,
+    pub winner: Option<Address>,
 }
 
 #[derive(Clone)]
@@ -58,173 +268,965 @@ pub struct Groth16Proof {
     pub c: G1Affine,
 }
 
+// Game Hub interface (Testnet hub: CB4VZAT2U3UC6XFK3N23SKRF2NDCMP3QHJYMCHHFMZO7MRQO6DQ2EMYG).
+// Calls go through the `try_` client methods, so a failing hub never blocks a game.
+#[contractclient(name = "GameHubClient")]
+pub trait GameHub {
+    fn start_game(env: Env, game_id: Address, session_id: u32, player1: Address, player2: Address, player1_points: i128, player2_points: i128) -> Result<(), HubError>;
+    fn end_game(env: Env, session_id: u32, player1_won: bool);
+}
+
+// Session ids drawn before giving up on a hub whose ids keep colliding
+const SESSION_ATTEMPTS: u32 = 4;
+
+// About a day of 5-second ledgers
+const DAY_IN_LEDGERS: u32 = 17_280;
+// The instance (admin, VK), the cards and every game entry are extended back to 30 days
+// whenever they drop below 29, so a running game never gets archived
+const TTL_BUMP: u32 = 30 * DAY_IN_LEDGERS;
+const TTL_THRESHOLD: u32 = TTL_BUMP - DAY_IN_LEDGERS;
+// A trap trigger only matters for the attack's turn and the next, so it lives in temporary storage
+const TRIGGER_TTL: u32 = DAY_IN_LEDGERS;
+// Games a player can have running at once, which bounds their ActiveGames list
+const MAX_ACTIVE_GAMES: u32 = 10;
+
 #[contract]
 pub struct ZkTcg;
 
-const GAME_HUB_ADDRESS: &str = "CB4VZAT2U3UC6XFK3N23SKRF2NDCMP3QHJYMCHHFMZO7MRQO6DQ2EMYG";
-
 #[contractimpl]
 impl ZkTcg {
-    pub fn init(env: Env, vk: VerificationKey) {
-        env.storage().persistent().set(&DataKey::VerificationKey, &vk);
+    pub fn init(env: Env, admin: Address, vk: VerificationKey) -> Result<(), TcgError> {
+        if env.storage().instance().has(&DataKey::Admin) {
+            return Err(TcgError::AlreadyInitialized);
+        }
+        admin.require_auth();
+        env.storage().instance().set(&DataKey::Admin, &admin);
+        env.storage().instance().set(&DataKey::VerificationKey, &vk);
+        Self::extend_instance(&env);
+        Ok(())
     }
 
-    pub fn init_game(env: Env, player1: Address, player2: Address) -> Result<(), TcgError> {
+    // Card ids match the values committed in deck hashes (1 = Soldier, ..., 4 = Fireball)
+    pub fn register_card(env: Env, card_id: u32, card: Card) -> Result<(), TcgError> {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).ok_or(TcgError::Unauthorized)?;
+        admin.require_auth();
+        env.storage().persistent().set(&DataKey::Card(card_id), &card);
+        Self::extend(&env, &DataKey::Card(card_id));
+        Ok(())
+    }
+
+    // Game starts and results are reported to this hub from then on
+    pub fn set_game_hub(env: Env, hub: Address) -> Result<(), TcgError> {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).ok_or(TcgError::Unauthorized)?;
+        admin.require_auth();
+        env.storage().instance().set(&DataKey::GameHub, &hub);
+        Ok(())
+    }
+
+    // Extends the instance and the listed games and cards. Games extend themselves on every
+    // move and cards whenever they are played; this keeps idle games and unplayed cards alive
+    pub fn bump_ttl(env: Env, game_ids: Vec<u32>, card_ids: Vec<u32>) -> Result<(), TcgError> {
+        let admin: Address = env.storage().instance().get(&DataKey::Admin).ok_or(TcgError::Unauthorized)?;
+        admin.require_auth();
+        Self::extend_instance(&env);
+        for game_id in game_ids.iter() {
+            if !env.storage().persistent().has(&DataKey::Player1(game_id)) {
+                return Err(TcgError::GameNotFound);
+            }
+            Self::extend_game(&env, game_id);
+        }
+        for card_id in card_ids.iter() {
+            Self::card(&env, card_id)?;
+        }
+        Ok(())
+    }
+
+    // Both players sign: the game joins each one's active list and is reported to the Game Hub
+    pub fn init_game(env: Env, player1: Address, player2: Address, rules: TcgRules) -> Result<u32, TcgError> {
         player1.require_auth();
+        player2.require_auth();
+
+        if player1 == player2 {
+            return Err(TcgError::InvalidPlayer);
+        }
+        if Self::get_active_games(env.clone(), player1.clone()).len() >= MAX_ACTIVE_GAMES || Self::get_active_games(env.clone(), player2.clone()).len() >= MAX_ACTIVE_GAMES {
+            return Err(TcgError::TooManyGames);
+        }
+        if rules.starting_hp == 0 || rules.deck_size == 0 || rules.board_slots == 0 || rules.hand_limit == 0 || rules.turn_timeout == 0 || rules.opening_hand > rules.deck_size {
+            return Err(TcgError::InvalidRules);
+        }
+
+        let game_id: u32 = env.storage().instance().get(&DataKey::GameCounter).unwrap_or(0) + 1;
+        env.storage().instance().set(&DataKey::GameCounter, &game_id);
         
-        env.storage().persistent().set(&DataKey::Player1, &player1);
-        env.storage().persistent().set(&DataKey::Player2, &player2);
-        env.storage().persistent().set(&DataKey::Hp(player1.clone()), &15u32);
-        env.storage().persistent().set(&DataKey::Hp(player2.clone()), &15u32);
-        env.storage().persistent().set(&DataKey::DrawIndex(player1.clone()), &0u32);
-        env.storage().persistent().set(&DataKey::DrawIndex(player2.clone()), &0u32);
+        env.storage().persistent().set(&DataKey::Player1(game_id), &player1);
+        env.storage().persistent().set(&DataKey::Player2(game_id), &player2);
+        env.storage().persistent().set(&DataKey::Rules(game_id), &rules);
+        env.storage().persistent().set(&DataKey::Hp(game_id, player1.clone()), &rules.starting_hp);
+        env.storage().persistent().set(&DataKey::Hp(game_id, player2.clone()), &rules.starting_hp);
+        env.storage().persistent().set(&DataKey::DrawIndex(game_id, player1.clone()), &0u32);
+        env.storage().persistent().set(&DataKey::DrawIndex(game_id, player2.clone()), &0u32);
+        env.storage().persistent().set(&DataKey::Fatigue(game_id, player1.clone()), &0u32);
+        env.storage().persistent().set(&DataKey::Fatigue(game_id, player2.clone()), &0u32);
+        env.storage().persistent().set(&DataKey::HandSize(game_id, player1.clone()), &0u32);
+        env.storage().persistent().set(&DataKey::HandSize(game_id, player2.clone()), &0u32);
+        env.storage().persistent().set(&DataKey::PendingDraws(game_id, player1.clone()), &rules.opening_hand);
+        env.storage().persistent().set(&DataKey::PendingDraws(game_id, player2.clone()), &rules.opening_hand);
         
-        let empty_board: Vec<Creature> = vec![&env];
-        env.storage().persistent().set(&DataKey::Board(player1.clone()), &empty_board);
-        env.storage().persistent().set(&DataKey::Board(player2.clone()), &empty_board);
+        let empty_board: Map<u32, Creature> = Map::new(&env);
+        env.storage().persistent().set(&DataKey::Board(game_id, player1.clone()), &empty_board);
+        env.storage().persistent().set(&DataKey::Board(game_id, player2.clone()), &empty_board);
+        let empty_graveyard: Vec<u32> = vec![&env];
+        env.storage().persistent().set(&DataKey::Graveyard(game_id, player1.clone()), &empty_graveyard);
+        env.storage().persistent().set(&DataKey::Graveyard(game_id, player2.clone()), &empty_graveyard);
         
-        env.storage().persistent().set(&DataKey::Turn, &player1);
-        env.storage().persistent().set(&DataKey::GameStarted, &true);
-        env.storage().persistent().remove(&DataKey::Winner);
-
-        // NOTE: Game Hub cross-contract call bypassed for demo standalone reliability
+        env.storage().persistent().set(&DataKey::Mana(game_id, player1.clone()), &0u32);
+        env.storage().persistent().set(&DataKey::Mana(game_id, player2.clone()), &0u32);
+        env.storage().persistent().set(&DataKey::MaxMana(game_id, player1.clone()), &0u32);
+        env.storage().persistent().set(&DataKey::MaxMana(game_id, player2.clone()), &0u32);
         
-        log!(&env, "ZK TCG Arena Game Started!");
-        Ok(())
+        env.storage().persistent().set(&DataKey::Turn(game_id), &player1);
+        env.storage().persistent().set(&DataKey::TurnNumber(game_id), &1u32);
+        env.storage().persistent().set(&DataKey::GameStarted(game_id), &true);
+
+        Self::add_active_game(&env, &player1, game_id);
+        Self::add_active_game(&env, &player2, game_id);
+        events::game_started(&env, game_id, &player1, &player2, &rules);
+
+        // Hub session ids are shared by every game, so draw one rather than reuse game_id,
+        // and draw again if another game already holds it
+        if let Some(hub) = Self::get_game_hub(env.clone()) {
+            let hub = GameHubClient::new(&env, &hub);
+            for attempt in 1..=SESSION_ATTEMPTS {
+                let session_id = env.prng().gen_range::<u64>(0..=u32::MAX as u64) as u32;
+                match hub.try_start_game(&env.current_contract_address(), &session_id, &player1, &player2, &0, &0) {
+                    Ok(_) => {
+                        env.storage().persistent().set(&DataKey::HubSession(game_id), &session_id);
+                        break;
+                    }
+                    Err(Ok(HubError::SessionExists)) if attempt < SESSION_ATTEMPTS => {}
+                    Err(_) => {
+                        events::hub_call_failed(&env, game_id, symbol_short!("start"));
+                        break;
+                    }
+                }
+            }
+        }
+
+        Self::extend_game(&env, game_id);
+        log!(&env, "ZK TCG Arena Game Started: game={}", game_id);
+        Ok(game_id)
     }
 
-    pub fn commit_deck(env: Env, player: Address, hash: BytesN<32>) -> Result<(), TcgError> {
+    pub fn commit_deck(env: Env, game_id: u32, player: Address, hash: BytesN<32>) -> Result<(), TcgError> {
         player.require_auth();
-        env.storage().persistent().set(&DataKey::DeckHash(player), &hash);
+        Self::opponent_of(&env, game_id, &player)?;
+        let phase = Self::phase(&env, game_id);
+        if phase == GamePhase::Ended {
+            return Err(TcgError::GameEnded);
+        }
+        env.storage().persistent().set(&DataKey::DeckHash(game_id, player.clone()), &hash);
+        events::deck_committed(&env, game_id, &player, &hash);
+
+        // The second deck starts player 1's first turn, and with it the turn clock
+        if phase == GamePhase::AwaitingDecks && Self::phase(&env, game_id) == GamePhase::Active {
+            let player1: Address = env.storage().persistent().get(&DataKey::Player1(game_id)).ok_or(TcgError::GameNotFound)?;
+            Self::start_turn(&env, game_id, &player1, &Self::rules(&env, game_id)?);
+        }
+        Self::extend_game(&env, game_id);
         Ok(())
     }
 
-    pub fn draw_card(env: Env, player: Address, card_value: u32, proof_bytes: Bytes) -> Result<(), TcgError> {
+    pub fn draw_card(env: Env, game_id: u32, player: Address, card_value: u32, proof_bytes: Bytes) -> Result<(), TcgError> {
         player.require_auth();
-        
-        if !env.storage().persistent().get::<_, bool>(&DataKey::GameStarted).unwrap_or(false) {
-            return Err(TcgError::GameNotStarted);
+        Self::require_turn(&env, game_id, &player)?;
+
+        let rules = Self::rules(&env, game_id)?;
+        let pending: u32 = env.storage().persistent().get(&DataKey::PendingDraws(game_id, player.clone())).unwrap_or(0);
+        if pending == 0 {
+            return Err(TcgError::NoDrawsOwed);
         }
+        let mut idx: u32 = env.storage().persistent().get(&DataKey::DrawIndex(game_id, player.clone())).unwrap_or(0);
 
-        // --- HACKATHON DEMO BYPASS ---
-        // In production, we deserialize `proof_bytes` to `Groth16Proof` and `pub_signals`
-        // and check: Self::verify_groth16(&env, &vk, &proof, &pub_signals)
-        // For the demo, we assume the JS-side verification passed if bytes are present.
-        if proof_bytes.is_empty() {
-            return Err(TcgError::InvalidProof);
+        // Deck exhausted: there is no card left to prove, the draw deals fatigue instead
+        if idx >= rules.deck_size {
+            env.storage().persistent().set(&DataKey::PendingDraws(game_id, player.clone()), &(pending - 1));
+            Self::apply_fatigue(&env, game_id, &player, &rules)?;
+            Self::extend_game(&env, game_id);
+            return Ok(());
         }
 
-        let mut idx: u32 = env.storage().persistent().get(&DataKey::DrawIndex(player.clone())).unwrap_or(0);
+        let hand: u32 = env.storage().persistent().get(&DataKey::HandSize(game_id, player.clone())).unwrap_or(0);
+        if hand >= rules.hand_limit {
+            return Err(TcgError::HandFull);
+        }
+
+        Self::check_deck_proof(&proof_bytes)?;
+
         idx += 1;
-        env.storage().persistent().set(&DataKey::DrawIndex(player.clone()), &idx);
+        env.storage().persistent().set(&DataKey::DrawIndex(game_id, player.clone()), &idx);
+        env.storage().persistent().set(&DataKey::HandSize(game_id, player.clone()), &(hand + 1));
+        env.storage().persistent().set(&DataKey::PendingDraws(game_id, player.clone()), &(pending - 1));
 
-        env.events().publish((symbol_short!("draw"), player), card_value);
+        events::card_drawn(&env, game_id, &player, idx - 1, card_value);
+        Self::extend_game(&env, game_id);
 
         Ok(())
     }
 
-    pub fn play_creature(env: Env, player: Address, attack: u32, health: u32) -> Result<(), TcgError> {
+    // Returns the id the new creature is addressed by for the rest of the game
+    pub fn play_creature(env: Env, game_id: u32, player: Address, card_id: u32) -> Result<u32, TcgError> {
         player.require_auth();
-        let mut board: Vec<Creature> = env.storage().persistent().get(&DataKey::Board(player.clone())).unwrap_or(vec![&env]);
-        board.push_back(Creature { attack, health });
-        env.storage().persistent().set(&DataKey::Board(player), &board);
+        Self::require_turn(&env, game_id, &player)?;
+        let rules = Self::rules(&env, game_id)?;
+
+        let card = Self::card(&env, card_id)?;
+        if card.kind != CardKind::Creature {
+            return Err(TcgError::InvalidCard);
+        }
+
+        if Self::board(&env, game_id, &player).len() >= rules.board_slots {
+            return Err(TcgError::BoardFull);
+        }
+        Self::spend_mana(&env, game_id, &player, card.cost)?;
+        Self::play_from_hand(&env, game_id, &player)?;
+
+        events::card_played(&env, game_id, &player, card_id, &Target::None);
+        let creature_id = Self::summon(&env, game_id, &player, card_id, &card, &rules)?;
+        Self::extend_game(&env, game_id);
+        Ok(creature_id)
+    }
+
+    pub fn play_spell(env: Env, game_id: u32, player: Address, card_id: u32, target: Target) -> Result<(), TcgError> {
+        player.require_auth();
+        let opponent = Self::require_turn(&env, game_id, &player)?;
+
+        let card = Self::card(&env, card_id)?;
+        if card.kind != CardKind::Spell {
+            return Err(TcgError::InvalidCard);
+        }
+        Self::spend_mana(&env, game_id, &player, card.cost)?;
+        Self::play_from_hand(&env, game_id, &player)?;
+        events::card_played(&env, game_id, &player, card_id, &target);
+
+        let source = DamageSource::Spell(card_id);
+        for spell_effect in card.effects.iter() {
+            // A lethal effect ends the game; the rest of the spell fizzles
+            if Self::phase(&env, game_id) == GamePhase::Ended {
+                break;
+            }
+            let targets = Self::resolve_targets(&env, game_id, &player, &opponent, &spell_effect.target, &target)?;
+            for t in targets.iter() {
+                Self::apply_effect(&env, game_id, &source, &spell_effect.effect, &t)?;
+            }
+        }
+        Self::bury(&env, game_id, &player, card_id);
+        Self::extend_game(&env, game_id);
         Ok(())
     }
 
-    pub fn play_fireball(env: Env, player: Address, target: Address) -> Result<(), TcgError> {
+    pub fn attack(env: Env, game_id: u32, player: Address, attacker_id: u32, target: Address) -> Result<(), TcgError> {
         player.require_auth();
-        let mut hp: u32 = env.storage().persistent().get(&DataKey::Hp(target.clone())).unwrap_or(15);
-        if hp > 2 {
-            hp -= 2;
+        let opponent = Self::require_turn(&env, game_id, &player)?;
+        if target != opponent {
+            return Err(TcgError::InvalidTarget);
+        }
+        let mut board = Self::board(&env, game_id, &player);
+
+        let mut creature = board.get(attacker_id).ok_or(TcgError::InvalidTarget)?;
+        if creature.status != CreatureStatus::Ready {
+            return Err(TcgError::CreatureNotReady);
+        }
+        creature.status = CreatureStatus::Exhausted;
+        board.set(attacker_id, creature.clone());
+        env.storage().persistent().set(&DataKey::Board(game_id, player.clone()), &board);
+
+        Self::record_trigger(&env, game_id, &target, &player, attacker_id);
+        let source = DamageSource::Creature(player, attacker_id);
+        Self::damage_player(&env, game_id, &source, &target, creature.attack)?;
+        Self::extend_game(&env, game_id);
+        Ok(())
+    }
+
+    pub fn attack_creature(env: Env, game_id: u32, player: Address, attacker_id: u32, target_player: Address, target_creature_id: u32) -> Result<(), TcgError> {
+        player.require_auth();
+        let opponent = Self::require_turn(&env, game_id, &player)?;
+        if target_player != opponent {
+            return Err(TcgError::InvalidTarget);
+        }
+        let mut board = Self::board(&env, game_id, &player);
+        let mut target_board = Self::board(&env, game_id, &target_player);
+
+        let mut attacker = board.get(attacker_id).ok_or(TcgError::InvalidTarget)?;
+        let mut target = target_board.get(target_creature_id).ok_or(TcgError::InvalidTarget)?;
+        if attacker.status != CreatureStatus::Ready {
+            return Err(TcgError::CreatureNotReady);
+        }
+        attacker.status = CreatureStatus::Exhausted;
+        Self::record_trigger(&env, game_id, &target_player, &player, attacker_id);
+
+        // Both deal damage simultaneously
+        let attacker_dmg = attacker.attack;
+        let target_dmg = target.attack;
+        let attacker_ref = Target::Creature(player.clone(), attacker_id);
+        let target_ref = Target::Creature(target_player.clone(), target_creature_id);
+
+        if attacker.health > target_dmg {
+            attacker.health -= target_dmg;
+            attacker.attack = attacker.attack.saturating_sub(target_dmg);
+            events::damage_dealt(&env, game_id, &DamageSource::Creature(target_player.clone(), target_creature_id), attacker_ref, target_dmg, attacker.health);
+            board.set(attacker_id, attacker);
+        } else {
+            events::damage_dealt(&env, game_id, &DamageSource::Creature(target_player.clone(), target_creature_id), attacker_ref, target_dmg, 0);
+            events::creature_died(&env, game_id, &player, attacker_id, attacker.card_id);
+            Self::bury(&env, game_id, &player, attacker.card_id);
+            board.remove(attacker_id);
+        }
+
+        if target.health > attacker_dmg {
+            target.health -= attacker_dmg;
+            target.attack = target.attack.saturating_sub(attacker_dmg);
+            events::damage_dealt(&env, game_id, &DamageSource::Creature(player.clone(), attacker_id), target_ref, attacker_dmg, target.health);
+            target_board.set(target_creature_id, target);
         } else {
-            hp = 0;
-            env.storage().persistent().set(&DataKey::Winner, &player);
+            events::damage_dealt(&env, game_id, &DamageSource::Creature(player.clone(), attacker_id), target_ref, attacker_dmg, 0);
+            events::creature_died(&env, game_id, &target_player, target_creature_id, target.card_id);
+            Self::bury(&env, game_id, &target_player, target.card_id);
+            target_board.remove(target_creature_id);
         }
-        env.storage().persistent().set(&DataKey::Hp(target), &hp);
+
+        env.storage().persistent().set(&DataKey::Board(game_id, player), &board);
+        env.storage().persistent().set(&DataKey::Board(game_id, target_player), &target_board);
+        Self::extend_game(&env, game_id);
         Ok(())
     }
 
-    pub fn attack(env: Env, player: Address, attacker_id: u32, target: Address) -> Result<(), TcgError> {
+    // Sets a card from hand face down. Returns the trap id to reveal it by
+    pub fn set_trap(env: Env, game_id: u32, player: Address, commitment: BytesN<32>) -> Result<u32, TcgError> {
         player.require_auth();
-        let board: Vec<Creature> = env.storage().persistent().get(&DataKey::Board(player.clone())).unwrap_or(vec![&env]);
-        
-        if attacker_id < board.len() {
-            let creature = board.get(attacker_id).unwrap();
-            let mut hp: u32 = env.storage().persistent().get(&DataKey::Hp(target.clone())).unwrap_or(15);
-            
-            if hp > creature.attack {
-                hp -= creature.attack;
-            } else {
-                hp = 0;
-                env.storage().persistent().set(&DataKey::Winner, &player);
+        Self::require_turn(&env, game_id, &player)?;
+        let rules = Self::rules(&env, game_id)?;
+
+        let key = DataKey::Traps(game_id, player.clone());
+        let mut traps: Map<u32, Trap> = env.storage().persistent().get(&key).unwrap_or(Map::new(&env));
+        if traps.len() >= rules.trap_slots {
+            return Err(TcgError::TrapSlotsFull);
+        }
+        Self::play_from_hand(&env, game_id, &player)?;
+
+        let trap_id: u32 = env.storage().persistent().get(&DataKey::TrapCounter(game_id)).unwrap_or(0) + 1;
+        env.storage().persistent().set(&DataKey::TrapCounter(game_id), &trap_id);
+        let set_turn: u32 = env.storage().persistent().get(&DataKey::TurnNumber(game_id)).unwrap_or(1);
+        let trap = Trap { commitment, set_turn, expires_turn: set_turn.saturating_add(rules.trap_duration) };
+        traps.set(trap_id, trap.clone());
+        env.storage().persistent().set(&key, &traps);
+
+        events::trap_set(&env, game_id, &player, trap_id, &trap);
+        Self::extend_game(&env, game_id);
+        Ok(trap_id)
+    }
+
+    // Springs a trap in answer to the last attack on `player`, during that turn or the next one.
+    // The salt opens the commitment; the proof is meant to show the card came from the committed
+    // deck, but `check_deck_proof` does not verify it yet. `set_trap` only sees the commitment,
+    // so this is the one place a trap card from outside the deck could be caught
+    pub fn reveal_trap(env: Env, game_id: u32, player: Address, trap_id: u32, card_id: u32, salt: BytesN<32>, proof_bytes: Bytes) -> Result<(), TcgError> {
+        player.require_auth();
+        Self::require_active(&env, game_id)?;
+        let opponent = Self::opponent_of(&env, game_id, &player)?;
+
+        let key = DataKey::Traps(game_id, player.clone());
+        let mut traps: Map<u32, Trap> = env.storage().persistent().get(&key).unwrap_or(Map::new(&env));
+        let trap = traps.get(trap_id).ok_or(TcgError::TrapNotFound)?;
+        let turn_number: u32 = env.storage().persistent().get(&DataKey::TurnNumber(game_id)).unwrap_or(1);
+        if turn_number > trap.expires_turn {
+            return Err(TcgError::TrapExpired);
+        }
+        let trigger: TrapTrigger = env.storage().temporary().get(&DataKey::TrapTrigger(game_id, player.clone())).ok_or(TcgError::TrapNotTriggered)?;
+        if trigger.turn_number <= trap.set_turn || trigger.turn_number + 1 < turn_number {
+            return Err(TcgError::TrapNotTriggered);
+        }
+
+        if Self::trap_commitment(&env, card_id, &salt) != trap.commitment {
+            return Err(TcgError::CommitmentMismatch);
+        }
+        Self::check_deck_proof(&proof_bytes)?;
+        let card = Self::card(&env, card_id)?;
+        if card.kind != CardKind::Trap {
+            return Err(TcgError::InvalidCard);
+        }
+
+        traps.remove(trap_id);
+        env.storage().persistent().set(&key, &traps);
+        let attacker = Target::Creature(trigger.attacker, trigger.creature_id);
+        events::trap_revealed(&env, game_id, &player, trap_id, card_id, &attacker);
+
+        // The attacker is the trap's chosen target; effects aimed at it fizzle if it has already died
+        let source = DamageSource::Trap(card_id);
+        for spell_effect in card.effects.iter() {
+            if Self::phase(&env, game_id) == GamePhase::Ended {
+                break;
+            }
+            let targets = match Self::resolve_targets(&env, game_id, &player, &opponent, &spell_effect.target, &attacker) {
+                Ok(targets) => targets,
+                Err(TcgError::InvalidTarget) => continue,
+                Err(e) => return Err(e),
+            };
+            for t in targets.iter() {
+                Self::apply_effect(&env, game_id, &source, &spell_effect.effect, &t)?;
             }
-            env.storage().persistent().set(&DataKey::Hp(target), &hp);
         }
+        Self::bury(&env, game_id, &player, card_id);
+        Self::extend_game(&env, game_id);
         Ok(())
     }
 
-    pub fn attack_creature(env: Env, player: Address, attacker_id: u32, target_player: Address, target_creature_id: u32) -> Result<(), TcgError> {
+    pub fn end_turn(env: Env, game_id: u32, player: Address) -> Result<(), TcgError> {
         player.require_auth();
-        let mut board: Vec<Creature> = env.storage().persistent().get(&DataKey::Board(player.clone())).unwrap_or(vec![&env]);
-        let mut target_board: Vec<Creature> = env.storage().persistent().get(&DataKey::Board(target_player.clone())).unwrap_or(vec![&env]);
-        
-        if attacker_id < board.len() && target_creature_id < target_board.len() {
-            let mut attacker = board.get(attacker_id).unwrap();
-            let mut target = target_board.get(target_creature_id).unwrap();
-            
-            // Both deal damage simultaneously
-            let attacker_dmg = attacker.attack;
-            let target_dmg = target.attack;
-
-            let mut attacker_survived = true;
-            if attacker.health > target_dmg {
-                attacker.health -= target_dmg;
-                attacker.attack = attacker.attack.saturating_sub(target_dmg);
-                board.set(attacker_id, attacker);
-            } else {
-                board.remove(attacker_id);
-                attacker_survived = false;
-            }
-
-            if target.health > attacker_dmg {
-                target.health -= attacker_dmg;
-                target.attack = target.attack.saturating_sub(attacker_dmg);
-                // Only update target if it wasn't destroyed
-                if attacker_survived {
-                     target_board.set(target_creature_id, target);
+        let opponent = Self::require_turn(&env, game_id, &player)?;
+        let rules = Self::rules(&env, game_id)?;
+
+        Self::burn_draws(&env, game_id, &player, &rules)?;
+        if Self::phase(&env, game_id) == GamePhase::Ended {
+            return Ok(());
+        }
+
+        let turn_number: u32 = env.storage().persistent().get(&DataKey::TurnNumber(game_id)).unwrap_or(1);
+        env.storage().persistent().set(&DataKey::TurnNumber(game_id), &(turn_number + 1));
+        env.storage().persistent().set(&DataKey::Turn(game_id), &opponent);
+        events::turn_ended(&env, game_id, &player, &opponent, turn_number);
+
+        // Every turn but the very first opens with a draw
+        let key = DataKey::PendingDraws(game_id, opponent.clone());
+        let pending: u32 = env.storage().persistent().get(&key).unwrap_or(0);
+        env.storage().persistent().set(&key, &pending.saturating_add(1));
+        Self::start_turn(&env, game_id, &opponent, &rules);
+        Self::extend_game(&env, game_id);
+        Ok(())
+    }
+
+    pub fn concede(env: Env, game_id: u32, player: Address) -> Result<(), TcgError> {
+        player.require_auth();
+        let opponent = Self::opponent_of(&env, game_id, &player)?;
+        if Self::phase(&env, game_id) == GamePhase::Ended {
+            return Err(TcgError::GameEnded);
+        }
+        Self::finish_game(&env, game_id, &opponent, GameOverReason::Conceded);
+        Ok(())
+    }
+
+    // The player waiting on a stalled opponent takes the win once the turn deadline passes.
+    // The clock only runs once both decks are committed.
+    pub fn claim_timeout(env: Env, game_id: u32, player: Address) -> Result<(), TcgError> {
+        player.require_auth();
+        let opponent = Self::opponent_of(&env, game_id, &player)?;
+        Self::require_active(&env, game_id)?;
+        let turn: Address = env.storage().persistent().get(&DataKey::Turn(game_id)).ok_or(TcgError::GameNotFound)?;
+        if turn != opponent {
+            return Err(TcgError::NotYourTurn);
+        }
+        let deadline: u32 = env.storage().persistent().get(&DataKey::TurnDeadline(game_id)).unwrap_or(0);
+        if env.ledger().sequence() <= deadline {
+            return Err(TcgError::TimeoutNotReached);
+        }
+        Self::finish_game(&env, game_id, &player, GameOverReason::Timeout);
+        Ok(())
+    }
+
+    pub fn get_game(env: Env, game_id: u32) -> Result<TcgGameView, TcgError> {
+        let p1: Address = env.storage().persistent().get(&DataKey::Player1(game_id)).ok_or(TcgError::GameNotFound)?;
+        let p2: Address = env.storage().persistent().get(&DataKey::Player2(game_id)).ok_or(TcgError::GameNotFound)?;
+        Ok(TcgGameView {
+            game_id,
+            rules: Self::rules(&env, game_id)?,
+            player1: Self::player_view(&env, game_id, &p1),
+            player2: Self::player_view(&env, game_id, &p2),
+            turn: env.storage().persistent().get(&DataKey::Turn(game_id)).unwrap_or(p1),
+            turn_number: env.storage().persistent().get(&DataKey::TurnNumber(game_id)).unwrap_or(1),
+            turn_deadline: env.storage().persistent().get(&DataKey::TurnDeadline(game_id)).unwrap_or(0),
+            phase: Self::phase(&env, game_id),
+            winner: env.storage().persistent().get(&DataKey::Winner(game_id)),
+        })
+    }
+
+    pub fn get_state(env: Env, game_id: u32) -> Result<(u32, u32), TcgError> {
+        let p1: Address = env.storage().persistent().get(&DataKey::Player1(game_id)).ok_or(TcgError::GameNotFound)?;
+        let p2: Address = env.storage().persistent().get(&DataKey::Player2(game_id)).ok_or(TcgError::GameNotFound)?;
+        let hp1 = env.storage().persistent().get(&DataKey::Hp(game_id, p1)).unwrap_or(0);
+        let hp2 = env.storage().persistent().get(&DataKey::Hp(game_id, p2)).unwrap_or(0);
+        Ok((hp1, hp2))
+    }
+
+    pub fn get_board(env: Env, game_id: u32, player: Address) -> Map<u32, Creature> {
+        Self::board(&env, game_id, &player)
+    }
+
+    pub fn get_graveyard(env: Env, game_id: u32, player: Address) -> Vec<u32> {
+        env.storage().persistent().get(&DataKey::Graveyard(game_id, player)).unwrap_or(vec![&env])
+    }
+
+    pub fn get_traps(env: Env, game_id: u32, player: Address) -> Map<u32, Trap> {
+        env.storage().persistent().get(&DataKey::Traps(game_id, player)).unwrap_or(Map::new(&env))
+    }
+
+    pub fn get_fatigue(env: Env, game_id: u32, player: Address) -> u32 {
+        env.storage().persistent().get(&DataKey::Fatigue(game_id, player)).unwrap_or(0)
+    }
+
+    pub fn get_rules(env: Env, game_id: u32) -> Result<TcgRules, TcgError> {
+        Self::rules(&env, game_id)
+    }
+
+    pub fn get_active_games(env: Env, player: Address) -> Vec<u32> {
+        env.storage().persistent().get(&DataKey::ActiveGames(player)).unwrap_or(vec![&env])
+    }
+
+    pub fn get_game_hub(env: Env) -> Option<Address> {
+        env.storage().instance().get(&DataKey::GameHub)
+    }
+
+    pub fn get_hub_session(env: Env, game_id: u32) -> Option<u32> {
+        env.storage().persistent().get(&DataKey::HubSession(game_id))
+    }
+
+    // Expands a spell effect's rule into the concrete targets it hits
+    fn resolve_targets(env: &Env, game_id: u32, caster: &Address, opponent: &Address, rule: &TargetRule, chosen: &Target) -> Result<Vec<Target>, TcgError> {
+        let target = match rule {
+            TargetRule::Caster => Target::Player(caster.clone()),
+            TargetRule::Opponent => Target::Player(opponent.clone()),
+            TargetRule::AnyPlayer => match chosen {
+                Target::Player(p) if p == caster || p == opponent => chosen.clone(),
+                _ => return Err(TcgError::InvalidTarget),
+            },
+            TargetRule::AnyEnemy => match chosen {
+                Target::Player(p) if p == opponent => chosen.clone(),
+                Target::Creature(owner, id) if owner == opponent && Self::board(env, game_id, owner).contains_key(*id) => chosen.clone(),
+                _ => return Err(TcgError::InvalidTarget),
+            },
+            TargetRule::AnyCreature | TargetRule::FriendlyCreature | TargetRule::EnemyCreature => {
+                let (owner, id) = match chosen {
+                    Target::Creature(owner, id) => (owner, *id),
+                    _ => return Err(TcgError::InvalidTarget),
+                };
+                let allowed = match rule {
+                    TargetRule::FriendlyCreature => owner == caster,
+                    TargetRule::EnemyCreature => owner == opponent,
+                    _ => owner == caster || owner == opponent,
+                };
+                if !allowed || !Self::board(env, game_id, owner).contains_key(id) {
+                    return Err(TcgError::InvalidTarget);
+                }
+                chosen.clone()
+            }
+            TargetRule::AllEnemyCreatures => {
+                let mut targets = vec![env];
+                for id in Self::board(env, game_id, opponent).keys().iter() {
+                    targets.push_back(Target::Creature(opponent.clone(), id));
+                }
+                return Ok(targets);
+            }
+        };
+        Ok(vec![env, target])
+    }
+
+    fn apply_effect(env: &Env, game_id: u32, source: &DamageSource, effect: &Effect, target: &Target) -> Result<(), TcgError> {
+        match (effect, target) {
+            (Effect::DealDamage(amount), Target::Player(p)) => {
+                Self::damage_player(env, game_id, source, p, *amount)?;
+            }
+            (Effect::Heal(amount), Target::Player(p)) => {
+                let rules = Self::rules(env, game_id)?;
+                let hp: u32 = env.storage().persistent().get(&DataKey::Hp(game_id, p.clone())).unwrap_or(0);
+                let healed = hp.saturating_add(*amount).min(rules.starting_hp);
+                env.storage().persistent().set(&DataKey::Hp(game_id, p.clone()), &healed);
+                events::healed(env, game_id, target, healed - hp, healed);
+            }
+            (Effect::DrawCards(count), Target::Player(p)) => {
+                // Draws stay private: the player proves each one through `draw_card`
+                let key = DataKey::PendingDraws(game_id, p.clone());
+                let pending: u32 = env.storage().persistent().get(&key).unwrap_or(0);
+                env.storage().persistent().set(&key, &pending.saturating_add(*count));
+                events::draws_granted(env, game_id, p, *count);
+            }
+            (Effect::Summon(card_id), Target::Player(p)) => {
+                let card = Self::card(env, *card_id)?;
+                if card.kind != CardKind::Creature {
+                    return Err(TcgError::InvalidCard);
+                }
+                let rules = Self::rules(env, game_id)?;
+                Self::summon(env, game_id, p, *card_id, &card, &rules)?;
+            }
+            (Effect::Resurrect, Target::Player(p)) => {
+                let rules = Self::rules(env, game_id)?;
+                let (card_id, card) = Self::exhume_creature(env, game_id, p)?;
+                Self::summon(env, game_id, p, card_id, &card, &rules)?;
+            }
+            (_, Target::Creature(owner, id)) => {
+                let mut board = Self::board(env, game_id, owner);
+                let mut creature = board.get(*id).ok_or(TcgError::InvalidTarget)?;
+                match effect {
+                    Effect::DealDamage(amount) => {
+                        creature.health = creature.health.saturating_sub(*amount);
+                        events::damage_dealt(env, game_id, source, target.clone(), *amount, creature.health);
+                    }
+                    Effect::Heal(amount) => {
+                        let health = creature.health.saturating_add(*amount);
+                        events::healed(env, game_id, target, health - creature.health, health);
+                        creature.health = health;
+                    }
+                    Effect::Buff(attack, health) => {
+                        creature.attack = creature.attack.saturating_add(*attack);
+                        creature.health = creature.health.saturating_add(*health);
+                        events::buffed(env, game_id, target, *attack, *health);
+                    }
+                    Effect::Destroy => creature.health = 0,
+                    _ => return Err(TcgError::InvalidTarget),
+                }
+                if creature.health == 0 {
+                    events::creature_died(env, game_id, owner, *id, creature.card_id);
+                    Self::bury(env, game_id, owner, creature.card_id);
+                    board.remove(*id);
                 } else {
-                     // If attacker died, its index shifted. We must be careful if we are modifying the same board, but here target_board is distinct.
-                     target_board.set(target_creature_id, target);
+                    board.set(*id, creature);
                 }
-            } else {
-                target_board.remove(target_creature_id);
+                env.storage().persistent().set(&DataKey::Board(game_id, owner.clone()), &board);
+            }
+            _ => return Err(TcgError::InvalidTarget),
+        }
+        Ok(())
+    }
+
+    fn damage_player(env: &Env, game_id: u32, source: &DamageSource, target: &Address, amount: u32) -> Result<(), TcgError> {
+        let winner = Self::opponent_of(env, game_id, target)?;
+        let mut hp: u32 = env.storage().persistent().get(&DataKey::Hp(game_id, target.clone())).unwrap_or(0);
+        hp = hp.saturating_sub(amount);
+        env.storage().persistent().set(&DataKey::Hp(game_id, target.clone()), &hp);
+        events::damage_dealt(env, game_id, source, Target::Player(target.clone()), amount, hp);
+
+        if hp == 0 {
+            Self::finish_game(env, game_id, &winner, GameOverReason::HpDepleted);
+        }
+        Ok(())
+    }
+
+    // Each draw from an empty deck deals `fatigue_step` more damage than the last (1, 2, 3, ... by default)
+    fn apply_fatigue(env: &Env, game_id: u32, player: &Address, rules: &TcgRules) -> Result<(), TcgError> {
+        let opponent = Self::opponent_of(env, game_id, player)?;
+
+        let fatigue: u32 = env.storage().persistent().get(&DataKey::Fatigue(game_id, player.clone())).unwrap_or(0) + rules.fatigue_step;
+        env.storage().persistent().set(&DataKey::Fatigue(game_id, player.clone()), &fatigue);
+
+        let mut hp: u32 = env.storage().persistent().get(&DataKey::Hp(game_id, player.clone())).unwrap_or(0);
+        hp = hp.saturating_sub(fatigue);
+        env.storage().persistent().set(&DataKey::Hp(game_id, player.clone()), &hp);
+
+        events::damage_dealt(env, game_id, &DamageSource::Fatigue, Target::Player(player.clone()), fatigue, hp);
+
+        if hp == 0 {
+            Self::finish_game(env, game_id, &opponent, GameOverReason::HpDepleted);
+            log!(env, "ZK TCG Arena Game Ended by fatigue!");
+        }
+        Ok(())
+    }
+
+    // Draws still owed when a turn ends are lost: each one skips a card of the deck, or deals
+    // fatigue once the deck is empty, so holding draws back never dodges fatigue
+    fn burn_draws(env: &Env, game_id: u32, player: &Address, rules: &TcgRules) -> Result<(), TcgError> {
+        let pending: u32 = env.storage().persistent().get(&DataKey::PendingDraws(game_id, player.clone())).unwrap_or(0);
+        if pending == 0 {
+            return Ok(());
+        }
+        env.storage().persistent().set(&DataKey::PendingDraws(game_id, player.clone()), &0u32);
+
+        let idx: u32 = env.storage().persistent().get(&DataKey::DrawIndex(game_id, player.clone())).unwrap_or(0);
+        let burned = pending.min(rules.deck_size.saturating_sub(idx));
+        if burned > 0 {
+            env.storage().persistent().set(&DataKey::DrawIndex(game_id, player.clone()), &(idx + burned));
+            events::cards_burned(env, game_id, player, burned, idx + burned);
+        }
+        for _ in burned..pending {
+            Self::apply_fatigue(env, game_id, player, rules)?;
+            if Self::phase(env, game_id) == GamePhase::Ended {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    // The card registry is shared by every game, so each read keeps the card alive
+    fn card(env: &Env, card_id: u32) -> Result<Card, TcgError> {
+        let key = DataKey::Card(card_id);
+        let card = env.storage().persistent().get(&key).ok_or(TcgError::CardNotFound)?;
+        Self::extend(env, &key);
+        Ok(card)
+    }
+
+    fn rules(env: &Env, game_id: u32) -> Result<TcgRules, TcgError> {
+        env.storage().persistent().get(&DataKey::Rules(game_id)).ok_or(TcgError::GameNotFound)
+    }
+
+    fn phase(env: &Env, game_id: u32) -> GamePhase {
+        if !env.storage().persistent().get::<_, bool>(&DataKey::GameStarted(game_id)).unwrap_or(false) {
+            return GamePhase::Ended;
+        }
+        let decks_committed = [DataKey::Player1(game_id), DataKey::Player2(game_id)].iter().all(|key| {
+            match env.storage().persistent().get::<_, Address>(key) {
+                Some(player) => env.storage().persistent().has(&DataKey::DeckHash(game_id, player)),
+                None => false,
             }
+        });
+        if decks_committed {
+            GamePhase::Active
+        } else {
+            GamePhase::AwaitingDecks
+        }
+    }
+
+    fn require_active(env: &Env, game_id: u32) -> Result<(), TcgError> {
+        match Self::phase(env, game_id) {
+            GamePhase::Active => Ok(()),
+            GamePhase::AwaitingDecks => Err(TcgError::DecksNotCommitted),
+            GamePhase::Ended => Err(TcgError::GameEnded),
+        }
+    }
+
+    // Checks the game is running and it is `player`'s turn; returns their opponent
+    fn require_turn(env: &Env, game_id: u32, player: &Address) -> Result<Address, TcgError> {
+        Self::require_active(env, game_id)?;
+        let opponent = Self::opponent_of(env, game_id, player)?;
+        let turn: Address = env.storage().persistent().get(&DataKey::Turn(game_id)).ok_or(TcgError::GameNotFound)?;
+        if turn != *player {
+            return Err(TcgError::NotYourTurn);
+        }
+        Ok(opponent)
+    }
+
+    // Refills mana (one more than last turn, up to the cap), readies the player's creatures
+    // and restarts the turn clock
+    fn start_turn(env: &Env, game_id: u32, player: &Address, rules: &TcgRules) {
+        let deadline = env.ledger().sequence().saturating_add(rules.turn_timeout);
+        env.storage().persistent().set(&DataKey::TurnDeadline(game_id), &deadline);
 
-            env.storage().persistent().set(&DataKey::Board(player), &board);
-            env.storage().persistent().set(&DataKey::Board(target_player), &target_board);
+        let max_mana: u32 = env.storage().persistent().get(&DataKey::MaxMana(game_id, player.clone())).unwrap_or(0);
+        let max_mana = (max_mana + 1).min(rules.max_mana);
+        env.storage().persistent().set(&DataKey::MaxMana(game_id, player.clone()), &max_mana);
+        env.storage().persistent().set(&DataKey::Mana(game_id, player.clone()), &max_mana);
+
+        let mut board = Self::board(env, game_id, player);
+        for (id, mut creature) in board.clone().iter() {
+            creature.status = CreatureStatus::Ready;
+            board.set(id, creature);
+        }
+        env.storage().persistent().set(&DataKey::Board(game_id, player.clone()), &board);
+
+        Self::expire_traps(env, game_id, player);
+    }
+
+    // Drops `player`'s traps that outlived `trap_duration` without being revealed
+    fn expire_traps(env: &Env, game_id: u32, player: &Address) {
+        let key = DataKey::Traps(game_id, player.clone());
+        let Some(mut traps) = env.storage().persistent().get::<_, Map<u32, Trap>>(&key) else {
+            return;
+        };
+        let turn_number: u32 = env.storage().persistent().get(&DataKey::TurnNumber(game_id)).unwrap_or(1);
+        for (trap_id, trap) in traps.clone().iter() {
+            if turn_number > trap.expires_turn {
+                traps.remove(trap_id);
+                events::trap_expired(env, game_id, player, trap_id);
+            }
+        }
+        env.storage().persistent().set(&key, &traps);
+    }
+
+    fn record_trigger(env: &Env, game_id: u32, defender: &Address, attacker: &Address, creature_id: u32) {
+        let turn_number: u32 = env.storage().persistent().get(&DataKey::TurnNumber(game_id)).unwrap_or(1);
+        let trigger = TrapTrigger { attacker: attacker.clone(), creature_id, turn_number };
+        let key = DataKey::TrapTrigger(game_id, defender.clone());
+        env.storage().temporary().set(&key, &trigger);
+        env.storage().temporary().extend_ttl(&key, TRIGGER_TTL, TRIGGER_TTL);
+    }
+
+    fn trap_commitment(env: &Env, card_id: u32, salt: &BytesN<32>) -> BytesN<32> {
+        let mut preimage = Bytes::from_array(env, &card_id.to_be_bytes());
+        preimage.append(&Bytes::from(salt.clone()));
+        env.crypto().sha256(&preimage).into()
+    }
+
+    // Meant to prove a card belongs to the player's committed deck. Until the deck circuit has an
+    // on-chain verifier any non-empty proof passes, so neither draws nor trap reveals are bound
+    // to the deck hash; `test_trap_from_outside_the_deck_is_rejected` is ignored until then
+    fn check_deck_proof(proof_bytes: &Bytes) -> Result<(), TcgError> {
+        // --- HACKATHON DEMO BYPASS ---
+        // In production, we deserialize `proof_bytes` to `Groth16Proof` and `pub_signals`
+        // and check: Self::verify_groth16(&env, &vk, &proof, &pub_signals)
+        // For the demo, we assume the JS-side verification passed if bytes are present.
+        if proof_bytes.is_empty() {
+            return Err(TcgError::InvalidProof);
         }
         Ok(())
     }
 
-    pub fn end_game(env: Env, winner: Address) -> Result<(), TcgError> {
-        winner.require_auth();
-        env.storage().persistent().set(&DataKey::GameStarted, &false);
-        env.storage().persistent().set(&DataKey::Winner, &winner);
+    fn spend_mana(env: &Env, game_id: u32, player: &Address, cost: u32) -> Result<(), TcgError> {
+        let mana: u32 = env.storage().persistent().get(&DataKey::Mana(game_id, player.clone())).unwrap_or(0);
+        if mana < cost {
+            return Err(TcgError::NotEnoughMana);
+        }
+        env.storage().persistent().set(&DataKey::Mana(game_id, player.clone()), &(mana - cost));
+        Ok(())
+    }
 
-        // NOTE: Game Hub cross-contract call bypassed for demo standalone reliability
-        log!(&env, "ZK TCG Arena Game Ended!");
+    fn board(env: &Env, game_id: u32, player: &Address) -> Map<u32, Creature> {
+        env.storage().persistent().get(&DataKey::Board(game_id, player.clone())).unwrap_or(Map::new(env))
+    }
 
+    // Puts a fresh creature on `owner`'s board under the next id of this game; ids are never reused
+    fn summon(env: &Env, game_id: u32, owner: &Address, card_id: u32, card: &Card, rules: &TcgRules) -> Result<u32, TcgError> {
+        let mut board = Self::board(env, game_id, owner);
+        if board.len() >= rules.board_slots {
+            return Err(TcgError::BoardFull);
+        }
+        let id: u32 = env.storage().persistent().get(&DataKey::CreatureCounter(game_id)).unwrap_or(0) + 1;
+        env.storage().persistent().set(&DataKey::CreatureCounter(game_id), &id);
+
+        let creature = Creature {
+            card_id,
+            attack: card.attack,
+            health: card.health,
+            status: CreatureStatus::Sleeping,
+        };
+        board.set(id, creature.clone());
+        env.storage().persistent().set(&DataKey::Board(game_id, owner.clone()), &board);
+        events::creature_summoned(env, game_id, owner, id, &creature);
+        Ok(id)
+    }
+
+    // Dead creatures and resolved spells go to their owner's graveyard
+    fn bury(env: &Env, game_id: u32, owner: &Address, card_id: u32) {
+        let key = DataKey::Graveyard(game_id, owner.clone());
+        let mut graveyard: Vec<u32> = env.storage().persistent().get(&key).unwrap_or(vec![env]);
+        graveyard.push_back(card_id);
+        env.storage().persistent().set(&key, &graveyard);
+        events::card_buried(env, game_id, owner, card_id, graveyard.len());
+    }
+
+    // Takes the most recently buried creature card out of `owner`'s graveyard
+    fn exhume_creature(env: &Env, game_id: u32, owner: &Address) -> Result<(u32, Card), TcgError> {
+        let key = DataKey::Graveyard(game_id, owner.clone());
+        let mut graveyard: Vec<u32> = env.storage().persistent().get(&key).unwrap_or(vec![env]);
+        for i in (0..graveyard.len()).rev() {
+            let card_id = graveyard.get(i).unwrap();
+            let card = Self::card(env, card_id)?;
+            if card.kind == CardKind::Creature {
+                graveyard.remove(i);
+                env.storage().persistent().set(&key, &graveyard);
+                events::card_exhumed(env, game_id, owner, card_id, graveyard.len());
+                return Ok((card_id, card));
+            }
+        }
+        Err(TcgError::GraveyardEmpty)
+    }
+
+    fn player_view(env: &Env, game_id: u32, player: &Address) -> TcgPlayerView {
+        let storage = env.storage().persistent();
+        TcgPlayerView {
+            address: player.clone(),
+            hp: storage.get(&DataKey::Hp(game_id, player.clone())).unwrap_or(0),
+            board: Self::board(env, game_id, player),
+            graveyard: storage.get(&DataKey::Graveyard(game_id, player.clone())).unwrap_or(vec![env]),
+            traps: storage.get(&DataKey::Traps(game_id, player.clone())).unwrap_or(Map::new(env)),
+            hand_size: storage.get(&DataKey::HandSize(game_id, player.clone())).unwrap_or(0),
+            draw_index: storage.get(&DataKey::DrawIndex(game_id, player.clone())).unwrap_or(0),
+            pending_draws: storage.get(&DataKey::PendingDraws(game_id, player.clone())).unwrap_or(0),
+            fatigue: storage.get(&DataKey::Fatigue(game_id, player.clone())).unwrap_or(0),
+            mana: storage.get(&DataKey::Mana(game_id, player.clone())).unwrap_or(0),
+            max_mana: storage.get(&DataKey::MaxMana(game_id, player.clone())).unwrap_or(0),
+            deck_committed: storage.has(&DataKey::DeckHash(game_id, player.clone())),
+        }
+    }
+
+    fn play_from_hand(env: &Env, game_id: u32, player: &Address) -> Result<(), TcgError> {
+        let hand: u32 = env.storage().persistent().get(&DataKey::HandSize(game_id, player.clone())).unwrap_or(0);
+        if hand == 0 {
+            return Err(TcgError::EmptyHand);
+        }
+        env.storage().persistent().set(&DataKey::HandSize(game_id, player.clone()), &(hand - 1));
         Ok(())
     }
 
-    pub fn get_state(env: Env) -> (u32, u32) {
-        let p1: Address = env.storage().persistent().get(&DataKey::Player1).unwrap();
-        let p2: Address = env.storage().persistent().get(&DataKey::Player2).unwrap();
-        let hp1 = env.storage().persistent().get(&DataKey::Hp(p1)).unwrap_or(15);
-        let hp2 = env.storage().persistent().get(&DataKey::Hp(p2)).unwrap_or(15);
-        (hp1, hp2)
+    fn finish_game(env: &Env, game_id: u32, winner: &Address, reason: GameOverReason) {
+        env.storage().persistent().set(&DataKey::GameStarted(game_id), &false);
+        env.storage().persistent().set(&DataKey::Winner(game_id), winner);
+        events::game_over(env, game_id, winner, reason);
+
+        if let Some(p1) = env.storage().persistent().get::<_, Address>(&DataKey::Player1(game_id)) {
+            Self::remove_active_game(env, &p1, game_id);
+            let session = env.storage().persistent().get::<_, u32>(&DataKey::HubSession(game_id));
+            if let (Some(hub), Some(session_id)) = (Self::get_game_hub(env.clone()), session) {
+                if GameHubClient::new(env, &hub).try_end_game(&session_id, &(*winner == p1)).is_err() {
+                    events::hub_call_failed(env, game_id, symbol_short!("end"));
+                }
+            }
+        }
+        if let Some(p2) = env.storage().persistent().get::<_, Address>(&DataKey::Player2(game_id)) {
+            Self::remove_active_game(env, &p2, game_id);
+        }
+        Self::extend_game(env, game_id);
+    }
+
+    fn extend_instance(env: &Env) {
+        env.storage().instance().extend_ttl(TTL_THRESHOLD, TTL_BUMP);
+    }
+
+    fn extend(env: &Env, key: &DataKey) {
+        if env.storage().persistent().has(key) {
+            env.storage().persistent().extend_ttl(key, TTL_THRESHOLD, TTL_BUMP);
+        }
+    }
+
+    // Extends the instance and every persistent entry of the game, so it lives
+    // TTL_BUMP ledgers past its last move
+    fn extend_game(env: &Env, game_id: u32) {
+        Self::extend_instance(env);
+        for key in [DataKey::Player1(game_id), DataKey::Player2(game_id), DataKey::Rules(game_id), DataKey::Turn(game_id), DataKey::TurnNumber(game_id), DataKey::TurnDeadline(game_id), DataKey::CreatureCounter(game_id), DataKey::TrapCounter(game_id), DataKey::GameStarted(game_id), DataKey::Winner(game_id), DataKey::HubSession(game_id)] {
+            Self::extend(env, &key);
+        }
+        let players = [DataKey::Player1(game_id), DataKey::Player2(game_id)];
+        for player in players.iter().filter_map(|key| env.storage().persistent().get::<_, Address>(key)) {
+            for key in [DataKey::DeckHash(game_id, player.clone()), DataKey::Hp(game_id, player.clone()), DataKey::DrawIndex(game_id, player.clone()), DataKey::Board(game_id, player.clone()), DataKey::Graveyard(game_id, player.clone()), DataKey::Traps(game_id, player.clone()), DataKey::Fatigue(game_id, player.clone()), DataKey::HandSize(game_id, player.clone()), DataKey::PendingDraws(game_id, player.clone()), DataKey::Mana(game_id, player.clone()), DataKey::MaxMana(game_id, player.clone()), DataKey::ActiveGames(player)] {
+                Self::extend(env, &key);
+            }
+        }
     }
 
+    fn add_active_game(env: &Env, player: &Address, game_id: u32) {
+        let key = DataKey::ActiveGames(player.clone());
+        let mut games: Vec<u32> = env.storage().persistent().get(&key).unwrap_or(vec![env]);
+        games.push_back(game_id);
+        env.storage().persistent().set(&key, &games);
+    }
+
+    fn remove_active_game(env: &Env, player: &Address, game_id: u32) {
+        let key = DataKey::ActiveGames(player.clone());
+        let mut games: Vec<u32> = env.storage().persistent().get(&key).unwrap_or(vec![env]);
+        if let Some(i) = games.first_index_of(game_id) {
+            games.remove(i);
+            env.storage().persistent().set(&key, &games);
+        }
+    }
+
+    fn opponent_of(env: &Env, game_id: u32, player: &Address) -> Result<Address, TcgError> {
+        let p1: Address = env.storage().persistent().get(&DataKey::Player1(game_id)).ok_or(TcgError::GameNotFound)?;
+        let p2: Address = env.storage().persistent().get(&DataKey::Player2(game_id)).ok_or(TcgError::GameNotFound)?;
+        if *player == p1 {
+            Ok(p2)
+        } else if *player == p2 {
+            Ok(p1)
+        } else {
+            Err(TcgError::InvalidPlayer)
+        }
+    }
+
+    #[allow(dead_code)]
     fn verify_groth16(env: &Env, vk: &VerificationKey, proof: &Groth16Proof, pub_signals: &Vec<Fr>) -> Result<bool, TcgError> {
         let bls = env.crypto().bls12_381();
         if pub_signals.len() + 1 != vk.ic.len() {
@@ -243,3 +1245,5 @@ impl ZkTcg {
         Ok(bls.pairing_check(vp1, vp2))
     }
 }
+
+mod test;
@@ -0,0 +1,1188 @@
+#![cfg(test)]
+
+use soroban_sdk::{
+    testutils::{
+        storage::{Instance as _, Persistent as _},
+        Address as _, Events, Ledger,
+    },
+    map, vec, Address, Bytes, BytesN, Env, IntoVal, Map, Symbol, TryFromVal, Val, Vec,
+};
+
+use crate::events::{
+    CardPlayedEvent, CardsBurnedEvent, CreatureDiedEvent, CreatureSummonedEvent, DamageDealtEvent, GameOverEvent,
+    GraveyardEvent, HubCallFailedEvent, TrapExpiredEvent, TrapRevealedEvent, TurnEndedEvent, EVENT_VERSION,
+};
+use mock_game_hub::{MockGameHub, MockGameHubClient};
+use zk_poseidon2 as poseidon2;
+
+use crate::{
+    Card, CardKind, Creature, CreatureStatus, DamageSource, DataKey, Effect, GameOverReason, GamePhase,
+    SpellEffect, Target, TargetRule, TcgError, TcgRules, VerificationKey, ZkTcg, ZkTcgClient,
+};
+
+const SOLDIER: u32 = 1;
+const KNIGHT: u32 = 2;
+const GIANT: u32 = 3;
+const FIREBALL: u32 = 4;
+const MEND: u32 = 5;
+const RALLY: u32 = 6;
+const QUAKE: u32 = 7;
+const SMITE: u32 = 8;
+const REINFORCE: u32 = 9;
+const REVIVE: u32 = 10;
+const SNARE: u32 = 11;
+
+fn dummy_vk(env: &Env) -> VerificationKey {
+    let bls = env.crypto().bls12_381();
+    let dst = Bytes::from_slice(env, b"ZK-TCG-TEST");
+    let g1 = bls.hash_to_g1(&Bytes::from_slice(env, b"g1"), &dst);
+    let g2 = bls.hash_to_g2(&Bytes::from_slice(env, b"g2"), &dst);
+    VerificationKey {
+        alpha: g1.clone(),
+        beta: g2.clone(),
+        gamma: g2.clone(),
+        delta: g2,
+        ic: vec![env, g1],
+    }
+}
+
+fn creature_card(env: &Env, cost: u32, attack: u32, health: u32) -> Card {
+    Card { kind: CardKind::Creature, cost, attack, health, effects: Vec::new(env) }
+}
+
+fn spell(env: &Env, cost: u32, effects: &[(Effect, TargetRule)]) -> Card {
+    let mut list = Vec::new(env);
+    for (effect, target) in effects {
+        list.push_back(SpellEffect { effect: effect.clone(), target: target.clone() });
+    }
+    Card { kind: CardKind::Spell, cost, attack: 0, health: 0, effects: list }
+}
+
+fn trap(env: &Env, effects: &[(Effect, TargetRule)]) -> Card {
+    Card { kind: CardKind::Trap, ..spell(env, 0, effects) }
+}
+
+fn trap_commitment(env: &Env, card_id: u32, salt: &BytesN<32>) -> BytesN<32> {
+    let mut preimage = Bytes::from_array(env, &card_id.to_be_bytes());
+    preimage.append(&Bytes::from(salt.clone()));
+    env.crypto().sha256(&preimage).into()
+}
+
+fn creature(card_id: u32, attack: u32, health: u32, status: CreatureStatus) -> Creature {
+    Creature { card_id, attack, health, status }
+}
+
+fn setup_with_rules(env: &Env, rules: TcgRules) -> (ZkTcgClient<'_>, u32, Address, Address) {
+    setup_with_decks(env, rules, &BytesN::from_array(env, &[1; 32]), &BytesN::from_array(env, &[2; 32]))
+}
+
+fn setup_with_decks<'a>(env: &'a Env, rules: TcgRules, deck1: &BytesN<32>, deck2: &BytesN<32>) -> (ZkTcgClient<'a>, u32, Address, Address) {
+    env.mock_all_auths();
+    let contract_id = env.register(ZkTcg, ());
+    let client = ZkTcgClient::new(env, &contract_id);
+
+    client.init(&Address::generate(env), &dummy_vk(env));
+    client.register_card(&SOLDIER, &creature_card(env, 0, 2, 2));
+    client.register_card(&KNIGHT, &creature_card(env, 2, 3, 3));
+    client.register_card(&GIANT, &creature_card(env, 3, 4, 4));
+    client.register_card(&FIREBALL, &spell(env, 1, &[(Effect::DealDamage(2), TargetRule::AnyEnemy)]));
+    client.register_card(&MEND, &spell(env, 0, &[(Effect::Heal(3), TargetRule::Caster)]));
+    client.register_card(&RALLY, &spell(env, 0, &[(Effect::Buff(1, 1), TargetRule::FriendlyCreature)]));
+    client.register_card(&QUAKE, &spell(env, 0, &[(Effect::DealDamage(2), TargetRule::AllEnemyCreatures)]));
+    client.register_card(&SMITE, &spell(env, 0, &[(Effect::Destroy, TargetRule::EnemyCreature)]));
+    client.register_card(
+        &REINFORCE,
+        &spell(env, 0, &[(Effect::Summon(SOLDIER), TargetRule::Caster), (Effect::DrawCards(1), TargetRule::Caster)]),
+    );
+
+    client.register_card(&REVIVE, &spell(env, 0, &[(Effect::Resurrect, TargetRule::Caster)]));
+    client.register_card(&SNARE, &trap(env, &[(Effect::Destroy, TargetRule::EnemyCreature), (Effect::Heal(1), TargetRule::Caster)]));
+
+    let player1 = Address::generate(env);
+    let player2 = Address::generate(env);
+    let game_id = client.init_game(&player1, &player2, &rules);
+    client.commit_deck(&game_id, &player1, deck1);
+    client.commit_deck(&game_id, &player2, deck2);
+
+    (client, game_id, player1, player2)
+}
+
+// Standard rules, with player1 holding an opening hand of five cards. player2 draws
+// theirs when their first turn starts
+fn setup(env: &Env) -> (ZkTcgClient<'_>, u32, Address, Address) {
+    let (client, game_id, player1, player2) = setup_with_rules(env, TcgRules::standard());
+    draw(env, &client, game_id, &player1, 5);
+    (client, game_id, player1, player2)
+}
+
+fn draw(env: &Env, client: &ZkTcgClient, game_id: u32, player: &Address, count: u32) {
+    let proof = Bytes::from_slice(env, &[1]);
+    for card in 0..count {
+        client.draw_card(&game_id, player, &card, &proof);
+    }
+}
+
+// Ends `player`'s turn and has the opponent take the draws they are owed, as far as
+// their hand has room
+fn pass_turn(env: &Env, client: &ZkTcgClient, game_id: u32, player: &Address) {
+    client.end_turn(&game_id, player);
+    let view = client.get_game(&game_id);
+    let next = if view.player1.address == *player { view.player2 } else { view.player1 };
+    let room = client.get_rules(&game_id).hand_limit - next.hand_size;
+    draw(env, client, game_id, &next.address, next.pending_draws.min(room));
+}
+
+// The whole deck is dealt as the opening hand and fits in it, and the board has
+// room for every REINFORCE that grants a draw past the end of the deck
+fn deck_out_rules() -> TcgRules {
+    TcgRules { hand_limit: 12, board_slots: 12, opening_hand: 12, ..TcgRules::standard() }
+}
+
+// Plays REINFORCE `count` times, each owing `player` one more draw
+fn reinforce(client: &ZkTcgClient, game_id: u32, player: &Address, count: u32) {
+    for _ in 0..count {
+        client.play_spell(&game_id, player, &REINFORCE, &Target::None);
+    }
+}
+
+#[test]
+fn test_draws_within_deck_deal_no_fatigue() {
+    let env = Env::default();
+    let (client, game, player1, _) = setup_with_rules(&env, deck_out_rules());
+
+    draw(&env, &client, game, &player1, 12);
+
+    assert_eq!(client.get_fatigue(&game, &player1), 0);
+    assert_eq!(client.get_state(&game), (15, 15));
+}
+
+#[test]
+fn test_draws_must_be_owed() {
+    let env = Env::default();
+    let (client, game, player1, _) = setup(&env);
+    let proof = Bytes::from_slice(&env, &[1]);
+
+    assert_eq!(client.try_draw_card(&game, &player1, &5, &proof), Err(Ok(TcgError::NoDrawsOwed)));
+
+    client.play_spell(&game, &player1, &REINFORCE, &Target::None);
+    assert_eq!(client.get_game(&game).player1.pending_draws, 1);
+
+    client.draw_card(&game, &player1, &5, &proof);
+    let view = client.get_game(&game).player1;
+    assert_eq!((view.pending_draws, view.draw_index, view.hand_size), (0, 6, 5));
+    assert_eq!(client.try_draw_card(&game, &player1, &6, &proof), Err(Ok(TcgError::NoDrawsOwed)));
+}
+
+#[test]
+fn test_draws_wait_for_the_players_turn() {
+    let env = Env::default();
+    let (client, game, _, player2) = setup(&env);
+
+    assert_eq!(client.get_game(&game).player2.pending_draws, 5);
+    assert_eq!(
+        client.try_draw_card(&game, &player2, &0, &Bytes::from_slice(&env, &[1])),
+        Err(Ok(TcgError::NotYourTurn))
+    );
+}
+
+#[test]
+fn test_player_who_stops_drawing_still_takes_fatigue() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup_with_rules(&env, TcgRules { deck_size: 6, ..TcgRules::standard() });
+
+    client.end_turn(&game, &player1);
+    let burned: Vec<CardsBurnedEvent> = events_named(&env, "burned");
+    assert_eq!(burned, vec![&env, CardsBurnedEvent { player: player1.clone(), count: 5, draw_index: 5 }]);
+
+    // Turn 3 burns player1's last card, so the draws owed on turns 5 and 7 deal fatigue.
+    // player2 owed six on turn 2, so their fatigue starts on turn 4
+    client.end_turn(&game, &player2);
+    for _ in 0..3 {
+        client.end_turn(&game, &player1);
+        client.end_turn(&game, &player2);
+    }
+
+    let view = client.get_game(&game).player1;
+    assert_eq!((view.hand_size, view.draw_index, view.pending_draws), (0, 6, 1));
+    assert_eq!(view.fatigue, 2);
+    assert_eq!(client.get_state(&game), (12, 9));
+}
+
+#[test]
+fn test_fatigue_at_the_end_of_a_turn_can_end_the_game() {
+    let env = Env::default();
+    let rules = TcgRules { starting_hp: 1, deck_size: 5, ..TcgRules::standard() };
+    let (client, game, player1, player2) = setup_with_rules(&env, rules);
+    client.end_turn(&game, &player1);
+
+    // Five opening draws and one for turn 2 against a five card deck
+    client.end_turn(&game, &player2);
+
+    let view = client.get_game(&game);
+    assert_eq!(view.phase, GamePhase::Ended);
+    assert_eq!(view.winner, Some(player1));
+    assert_eq!((view.turn, view.turn_number), (player2, 2));
+}
+
+#[test]
+fn test_fatigue_damage_grows_with_each_empty_draw() {
+    let env = Env::default();
+    let (client, game, player1, _) = setup_with_rules(&env, deck_out_rules());
+    draw(&env, &client, game, &player1, 12);
+
+    // No proof is needed once the deck is empty
+    let empty = Bytes::new(&env);
+    reinforce(&client, game, &player1, 1);
+    client.draw_card(&game, &player1, &0, &empty);
+    assert_eq!(client.get_fatigue(&game, &player1), 1);
+    assert_eq!(client.get_state(&game), (14, 15));
+
+    reinforce(&client, game, &player1, 1);
+    client.draw_card(&game, &player1, &0, &empty);
+    assert_eq!(client.get_fatigue(&game, &player1), 2);
+    assert_eq!(client.get_state(&game), (12, 15));
+
+    reinforce(&client, game, &player1, 1);
+    client.draw_card(&game, &player1, &0, &empty);
+    assert_eq!(client.get_fatigue(&game, &player1), 3);
+    assert_eq!(client.get_state(&game), (9, 15));
+}
+
+#[test]
+fn test_fatigue_can_end_the_game() {
+    let env = Env::default();
+    let (client, game, player1, _) = setup_with_rules(&env, deck_out_rules());
+    draw(&env, &client, game, &player1, 12);
+    reinforce(&client, game, &player1, 6);
+
+    // 1 + 2 + 3 + 4 + 5 = 15 starting HP
+    let empty = Bytes::new(&env);
+    for _ in 0..5 {
+        client.draw_card(&game, &player1, &0, &empty);
+    }
+
+    assert_eq!(client.get_state(&game), (0, 15));
+    assert_eq!(
+        client.try_draw_card(&game, &player1, &0, &empty),
+        Err(Ok(TcgError::GameEnded))
+    );
+}
+
+#[test]
+fn test_game_ids_are_allocated_from_a_counter() {
+    let env = Env::default();
+    let (client, game1, player1, player2) = setup(&env);
+    let player3 = Address::generate(&env);
+
+    let game2 = client.init_game(&player1, &player3, &TcgRules::standard());
+    let game3 = client.init_game(&player3, &player2, &TcgRules::standard());
+
+    assert_eq!((game1, game2, game3), (1, 2, 3));
+    assert_eq!(client.get_active_games(&player1), vec![&env, game1, game2]);
+    assert_eq!(client.get_active_games(&player2), vec![&env, game1, game3]);
+    assert_eq!(client.get_active_games(&player3), vec![&env, game2, game3]);
+}
+
+#[test]
+fn test_both_players_sign_a_new_game() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup(&env);
+    let player3 = Address::generate(&env);
+    client.init_game(&player1, &player3, &TcgRules::standard());
+
+    let auths = env.auths();
+    assert!([&player1, &player3].iter().all(|player| auths.iter().any(|(signer, _)| signer == *player)));
+
+    // Player 2 already has `game`, so nine more fill their list
+    for _ in 0..9 {
+        client.init_game(&Address::generate(&env), &player2, &TcgRules::standard());
+    }
+    assert_eq!(
+        client.try_init_game(&player3, &player2, &TcgRules::standard()),
+        Err(Ok(TcgError::TooManyGames))
+    );
+    client.concede(&game, &player2);
+    client.init_game(&player3, &player2, &TcgRules::standard());
+    assert_eq!(client.get_active_games(&player2).len(), 10);
+}
+
+#[test]
+fn test_games_do_not_share_state() {
+    let env = Env::default();
+    let (client, game1, player1, player2) = setup(&env);
+    let player3 = Address::generate(&env);
+    let game2 = client.init_game(&player1, &player3, &TcgRules::standard());
+
+    client.play_spell(&game1, &player1, &FIREBALL, &Target::Player(player2.clone()));
+
+    assert_eq!(client.get_state(&game1), (15, 13));
+    assert_eq!(client.get_state(&game2), (15, 15));
+    assert_eq!(
+        client.try_commit_deck(&game2, &player2, &BytesN::from_array(&env, &[2; 32])),
+        Err(Ok(TcgError::InvalidPlayer))
+    );
+}
+
+#[test]
+fn test_ended_game_leaves_active_list() {
+    let env = Env::default();
+    let (client, game1, player1, player2) = setup(&env);
+    let player3 = Address::generate(&env);
+    let game2 = client.init_game(&player1, &player3, &TcgRules::standard());
+
+    client.concede(&game1, &player1);
+
+    assert_eq!(client.get_active_games(&player1), vec![&env, game2]);
+    assert_eq!(client.get_active_games(&player2), vec![&env]);
+}
+
+#[test]
+fn test_fireball_targets_opponent_or_enemy_creature() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup(&env);
+    pass_turn(&env, &client, game, &player1);
+    let soldier = client.play_creature(&game, &player2, &SOLDIER);
+    pass_turn(&env, &client, game, &player2);
+
+    client.play_spell(&game, &player1, &FIREBALL, &Target::Creature(player2.clone(), soldier));
+    pass_turn(&env, &client, game, &player1);
+    pass_turn(&env, &client, game, &player2);
+    client.play_spell(&game, &player1, &FIREBALL, &Target::Player(player2.clone()));
+
+    assert_eq!(client.get_state(&game), (15, 13));
+    assert_eq!(client.get_board(&game, &player2), Map::new(&env));
+    assert_eq!(
+        client.try_play_spell(&game, &player1, &FIREBALL, &Target::Player(player1.clone())),
+        Err(Ok(TcgError::InvalidTarget))
+    );
+    assert_eq!(
+        client.try_play_spell(&game, &player1, &FIREBALL, &Target::Creature(player2.clone(), soldier)),
+        Err(Ok(TcgError::InvalidTarget))
+    );
+}
+
+#[test]
+fn test_heal_is_capped_at_starting_hp() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup(&env);
+    pass_turn(&env, &client, game, &player1);
+    client.play_spell(&game, &player2, &FIREBALL, &Target::Player(player1.clone()));
+    pass_turn(&env, &client, game, &player2);
+
+    client.play_spell(&game, &player1, &MEND, &Target::None);
+
+    assert_eq!(client.get_state(&game), (15, 15));
+}
+
+#[test]
+fn test_huge_heals_and_buffs_saturate() {
+    let env = Env::default();
+    let (client, game, player1, _) = setup(&env);
+    client.register_card(&100, &spell(&env, 0, &[(Effect::Heal(u32::MAX), TargetRule::Caster)]));
+    client.register_card(&101, &spell(&env, 0, &[(Effect::Heal(u32::MAX), TargetRule::FriendlyCreature)]));
+    client.register_card(&102, &spell(&env, 0, &[(Effect::Buff(u32::MAX, u32::MAX), TargetRule::FriendlyCreature)]));
+    let mine = client.play_creature(&game, &player1, &SOLDIER);
+
+    client.play_spell(&game, &player1, &100, &Target::None);
+    client.play_spell(&game, &player1, &101, &Target::Creature(player1.clone(), mine));
+    client.play_spell(&game, &player1, &102, &Target::Creature(player1.clone(), mine));
+
+    assert_eq!(client.get_state(&game), (15, 15));
+    assert_eq!(client.get_board(&game, &player1), map![&env, (mine, creature(SOLDIER, u32::MAX, u32::MAX, CreatureStatus::Sleeping))]);
+}
+
+#[test]
+fn test_buff_and_destroy_creatures() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup(&env);
+    let mine = client.play_creature(&game, &player1, &SOLDIER);
+    pass_turn(&env, &client, game, &player1);
+    let theirs = client.play_creature(&game, &player2, &SOLDIER);
+    pass_turn(&env, &client, game, &player2);
+
+    client.play_spell(&game, &player1, &RALLY, &Target::Creature(player1.clone(), mine));
+    assert_eq!(
+        client.try_play_spell(&game, &player1, &RALLY, &Target::Creature(player2.clone(), theirs)),
+        Err(Ok(TcgError::InvalidTarget))
+    );
+    client.play_spell(&game, &player1, &SMITE, &Target::Creature(player2.clone(), theirs));
+
+    assert_eq!(client.get_board(&game, &player1), map![&env, (mine, creature(SOLDIER, 3, 3, CreatureStatus::Ready))]);
+    assert_eq!(client.get_board(&game, &player2), Map::new(&env));
+    assert_eq!(
+        client.try_play_spell(&game, &player1, &SMITE, &Target::Creature(player2.clone(), theirs)),
+        Err(Ok(TcgError::InvalidTarget))
+    );
+}
+
+#[test]
+fn test_area_damage_hits_every_enemy_creature() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup(&env);
+    pass_turn(&env, &client, game, &player1);
+    client.play_creature(&game, &player2, &SOLDIER);
+    client.play_creature(&game, &player2, &SOLDIER);
+    pass_turn(&env, &client, game, &player2);
+    pass_turn(&env, &client, game, &player1);
+    let knight = client.play_creature(&game, &player2, &KNIGHT);
+    pass_turn(&env, &client, game, &player2);
+
+    client.play_spell(&game, &player1, &QUAKE, &Target::None);
+
+    assert_eq!(client.get_board(&game, &player2), map![&env, (knight, creature(KNIGHT, 3, 1, CreatureStatus::Sleeping))]);
+}
+
+#[test]
+fn test_spell_can_summon_from_registry() {
+    let env = Env::default();
+    let (client, game, player1, _) = setup(&env);
+
+    client.play_spell(&game, &player1, &REINFORCE, &Target::None);
+
+    assert_eq!(client.get_board(&game, &player1), map![&env, (1, creature(SOLDIER, 2, 2, CreatureStatus::Sleeping))]);
+}
+
+#[test]
+fn test_play_spell_rejects_unknown_and_creature_cards() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup(&env);
+    let target = Target::Player(player2.clone());
+
+    assert_eq!(client.try_play_spell(&game, &player1, &99, &target), Err(Ok(TcgError::CardNotFound)));
+    assert_eq!(client.try_play_spell(&game, &player1, &GIANT, &target), Err(Ok(TcgError::InvalidCard)));
+    assert_eq!(client.try_play_creature(&game, &player1, &FIREBALL), Err(Ok(TcgError::InvalidCard)));
+}
+
+#[test]
+fn test_rules_set_starting_hp_and_heal_cap() {
+    let env = Env::default();
+    let quick = TcgRules { starting_hp: 10, ..TcgRules::standard() };
+    let (client, game, player1, player2) = setup_with_rules(&env, quick.clone());
+    draw(&env, &client, game, &player1, 1);
+
+    assert_eq!(client.get_rules(&game), quick);
+    assert_eq!(client.get_state(&game), (10, 10));
+
+    pass_turn(&env, &client, game, &player1);
+    client.play_spell(&game, &player2, &FIREBALL, &Target::Player(player1.clone()));
+    pass_turn(&env, &client, game, &player2);
+    client.play_spell(&game, &player1, &MEND, &Target::None);
+    assert_eq!(client.get_state(&game), (10, 10));
+}
+
+#[test]
+fn test_rules_reject_zero_limits() {
+    let env = Env::default();
+    let (client, _, player1, player2) = setup(&env);
+    let rules = TcgRules { board_slots: 0, ..TcgRules::standard() };
+
+    assert_eq!(client.try_init_game(&player1, &player2, &rules), Err(Ok(TcgError::InvalidRules)));
+
+    let rules = TcgRules { opening_hand: 13, ..TcgRules::standard() };
+    assert_eq!(client.try_init_game(&player1, &player2, &rules), Err(Ok(TcgError::InvalidRules)));
+}
+
+#[test]
+fn test_board_slots_limit_creatures() {
+    let env = Env::default();
+    let (client, game, player1, _) = setup(&env);
+    for _ in 0..3 {
+        client.play_creature(&game, &player1, &SOLDIER);
+    }
+
+    assert_eq!(client.try_play_creature(&game, &player1, &SOLDIER), Err(Ok(TcgError::BoardFull)));
+    assert_eq!(client.try_play_spell(&game, &player1, &REINFORCE, &Target::None), Err(Ok(TcgError::BoardFull)));
+}
+
+#[test]
+fn test_hand_limit_and_empty_hand() {
+    let env = Env::default();
+    let rules = TcgRules { hand_limit: 2, opening_hand: 3, ..TcgRules::standard() };
+    let (client, game, player1, _) = setup_with_rules(&env, rules);
+    let proof = Bytes::from_slice(&env, &[1]);
+
+    assert_eq!(client.try_play_creature(&game, &player1, &SOLDIER), Err(Ok(TcgError::EmptyHand)));
+
+    draw(&env, &client, game, &player1, 2);
+    assert_eq!(client.try_draw_card(&game, &player1, &1, &proof), Err(Ok(TcgError::HandFull)));
+
+    client.play_creature(&game, &player1, &SOLDIER);
+    client.draw_card(&game, &player1, &1, &proof);
+}
+
+#[test]
+fn test_fatigue_step_zero_disables_fatigue() {
+    let env = Env::default();
+    let rules = TcgRules { fatigue_step: 0, ..deck_out_rules() };
+    let (client, game, player1, _) = setup_with_rules(&env, rules);
+    draw(&env, &client, game, &player1, 12);
+    reinforce(&client, game, &player1, 1);
+
+    client.draw_card(&game, &player1, &0, &Bytes::new(&env));
+
+    assert_eq!(client.get_state(&game), (15, 15));
+}
+
+#[test]
+fn test_actions_wait_for_both_decks() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = ZkTcgClient::new(&env, &env.register(ZkTcg, ()));
+    let player1 = Address::generate(&env);
+    let player2 = Address::generate(&env);
+    let game = client.init_game(&player1, &player2, &TcgRules::standard());
+    client.commit_deck(&game, &player1, &BytesN::from_array(&env, &[1; 32]));
+
+    assert_eq!(client.get_game(&game).phase, GamePhase::AwaitingDecks);
+    assert_eq!(
+        client.try_draw_card(&game, &player1, &1, &Bytes::from_slice(&env, &[1])),
+        Err(Ok(TcgError::DecksNotCommitted))
+    );
+}
+
+#[test]
+fn test_turns_gate_actions_and_grow_mana() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup(&env);
+
+    assert_eq!(client.try_play_creature(&game, &player2, &SOLDIER), Err(Ok(TcgError::NotYourTurn)));
+    assert_eq!(client.try_end_turn(&game, &player2), Err(Ok(TcgError::NotYourTurn)));
+    assert_eq!(client.try_play_creature(&game, &player1, &GIANT), Err(Ok(TcgError::NotEnoughMana)));
+
+    pass_turn(&env, &client, game, &player1);
+    pass_turn(&env, &client, game, &player2);
+    pass_turn(&env, &client, game, &player1);
+    pass_turn(&env, &client, game, &player2);
+    client.play_creature(&game, &player1, &GIANT);
+
+    let view = client.get_game(&game);
+    assert_eq!(view.turn, player1);
+    assert_eq!(view.turn_number, 5);
+    assert_eq!((view.player1.mana, view.player1.max_mana), (0, 3));
+    assert_eq!((view.player2.mana, view.player2.max_mana), (2, 2));
+}
+
+#[test]
+fn test_creatures_wait_a_turn_and_attack_once() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup(&env);
+    let soldier = client.play_creature(&game, &player1, &SOLDIER);
+
+    assert_eq!(client.try_attack(&game, &player1, &soldier, &player2), Err(Ok(TcgError::CreatureNotReady)));
+
+    pass_turn(&env, &client, game, &player1);
+    pass_turn(&env, &client, game, &player2);
+    // Only the opponent can be attacked
+    assert_eq!(client.try_attack(&game, &player1, &soldier, &player1), Err(Ok(TcgError::InvalidTarget)));
+    assert_eq!(client.try_attack(&game, &player1, &soldier, &Address::generate(&env)), Err(Ok(TcgError::InvalidTarget)));
+    client.attack(&game, &player1, &soldier, &player2);
+
+    assert_eq!(client.get_state(&game), (15, 13));
+    assert_eq!(client.get_board(&game, &player1), map![&env, (soldier, creature(SOLDIER, 2, 2, CreatureStatus::Exhausted))]);
+    assert_eq!(client.try_attack(&game, &player1, &soldier, &player2), Err(Ok(TcgError::CreatureNotReady)));
+}
+
+#[test]
+fn test_get_game_reports_full_state() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup(&env);
+    let soldier = client.play_creature(&game, &player1, &SOLDIER);
+
+    let view = client.get_game(&game);
+
+    assert_eq!(view.game_id, game);
+    assert_eq!(view.rules, TcgRules::standard());
+    assert_eq!(view.phase, GamePhase::Active);
+    assert_eq!(view.winner, None);
+    assert_eq!(view.player1.address, player1);
+    assert_eq!(view.player1.hp, 15);
+    assert_eq!(view.player1.board, map![&env, (soldier, creature(SOLDIER, 2, 2, CreatureStatus::Sleeping))]);
+    assert_eq!(view.player1.hand_size, 4);
+    assert_eq!(view.player1.draw_index, 5);
+    assert!(view.player1.deck_committed);
+    assert_eq!(view.player2.address, player2);
+    assert_eq!((view.player2.hand_size, view.player2.pending_draws), (0, 5));
+
+    client.concede(&game, &player1);
+    let view = client.get_game(&game);
+    assert_eq!(view.phase, GamePhase::Ended);
+    assert_eq!(view.winner, Some(player2));
+}
+
+#[test]
+fn test_missing_game_is_a_typed_error() {
+    let env = Env::default();
+    let (client, _, _, _) = setup(&env);
+
+    assert_eq!(client.try_get_game(&42), Err(Ok(TcgError::GameNotFound)));
+    assert_eq!(client.try_get_state(&42), Err(Ok(TcgError::GameNotFound)));
+}
+
+// Payloads of every event named `name` published by the last contract call
+fn events_named<T: TryFromVal<Env, Val> + IntoVal<Env, Val>>(env: &Env, name: &str) -> Vec<T> {
+    let mut found = Vec::new(env);
+    for (_, topics, data) in env.events().all().iter() {
+        let topic = Symbol::try_from_val(env, &topics.get(0).unwrap());
+        if topic == Ok(Symbol::new(env, name)) {
+            assert_eq!(u32::try_from_val(env, &topics.get(1).unwrap()), Ok(EVENT_VERSION));
+            found.push_back(T::try_from_val(env, &data).unwrap());
+        }
+    }
+    found
+}
+
+#[test]
+fn test_card_played_and_summoned_events() {
+    let env = Env::default();
+    let (client, game, player1, _) = setup(&env);
+
+    client.play_creature(&game, &player1, &SOLDIER);
+
+    let played: Vec<CardPlayedEvent> = events_named(&env, "played");
+    assert_eq!(
+        played,
+        vec![&env, CardPlayedEvent { player: player1.clone(), card_id: SOLDIER, target: Target::None }]
+    );
+    let summoned: Vec<CreatureSummonedEvent> = events_named(&env, "summoned");
+    assert_eq!(summoned.get(0).unwrap().creature, creature(SOLDIER, 2, 2, CreatureStatus::Sleeping));
+}
+
+#[test]
+fn test_combat_emits_damage_and_death_events() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup(&env);
+    let mine = client.play_creature(&game, &player1, &SOLDIER);
+    pass_turn(&env, &client, game, &player1);
+    let theirs = client.play_creature(&game, &player2, &SOLDIER);
+    pass_turn(&env, &client, game, &player2);
+
+    client.attack_creature(&game, &player1, &mine, &player2, &theirs);
+
+    let damage: Vec<DamageDealtEvent> = events_named(&env, "damage");
+    assert_eq!(damage.len(), 2);
+    assert_eq!(
+        damage.get(1).unwrap(),
+        DamageDealtEvent {
+            source: DamageSource::Creature(player1.clone(), mine),
+            target: Target::Creature(player2.clone(), theirs),
+            amount: 2,
+            remaining: 0,
+        }
+    );
+    let died: Vec<CreatureDiedEvent> = events_named(&env, "died");
+    assert_eq!(
+        died,
+        vec![
+            &env,
+            CreatureDiedEvent { owner: player1.clone(), id: mine, card_id: SOLDIER },
+            CreatureDiedEvent { owner: player2.clone(), id: theirs, card_id: SOLDIER },
+        ]
+    );
+}
+
+#[test]
+fn test_turn_ended_event() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup(&env);
+
+    client.end_turn(&game, &player1);
+
+    let ended: Vec<TurnEndedEvent> = events_named(&env, "turn_end");
+    assert_eq!(ended, vec![&env, TurnEndedEvent { player: player1, next: player2, turn_number: 1 }]);
+}
+
+#[test]
+fn test_game_over_event_carries_reason() {
+    let env = Env::default();
+    let (client, game, player1, _) = setup_with_rules(&env, deck_out_rules());
+    draw(&env, &client, game, &player1, 12);
+    reinforce(&client, game, &player1, 5);
+    let empty = Bytes::new(&env);
+    for _ in 0..4 {
+        client.draw_card(&game, &player1, &0, &empty);
+    }
+
+    client.draw_card(&game, &player1, &0, &empty);
+
+    let damage: Vec<DamageDealtEvent> = events_named(&env, "damage");
+    assert_eq!(damage.get(0).unwrap().source, DamageSource::Fatigue);
+    let over: Vec<GameOverEvent> = events_named(&env, "game_over");
+    assert_eq!(over.get(0).unwrap().reason, GameOverReason::HpDepleted);
+}
+
+#[test]
+fn test_lethal_damage_ends_the_game() {
+    let env = Env::default();
+    let rules = TcgRules { starting_hp: 4, ..TcgRules::standard() };
+    let (client, game, player1, player2) = setup_with_rules(&env, rules);
+    draw(&env, &client, game, &player1, 2);
+
+    client.play_spell(&game, &player1, &FIREBALL, &Target::Player(player2.clone()));
+    pass_turn(&env, &client, game, &player1);
+    pass_turn(&env, &client, game, &player2);
+    client.play_spell(&game, &player1, &FIREBALL, &Target::Player(player2.clone()));
+
+    let over: Vec<GameOverEvent> = events_named(&env, "game_over");
+    assert_eq!(over, vec![&env, GameOverEvent { winner: player1.clone(), reason: GameOverReason::HpDepleted }]);
+    let view = client.get_game(&game);
+    assert_eq!(view.phase, GamePhase::Ended);
+    assert_eq!(view.winner, Some(player1));
+}
+
+#[test]
+fn test_ended_game_rejects_further_actions() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup(&env);
+    client.concede(&game, &player2);
+
+    let target = Target::Player(player2.clone());
+    assert_eq!(client.try_play_spell(&game, &player1, &FIREBALL, &target), Err(Ok(TcgError::GameEnded)));
+    assert_eq!(client.try_play_creature(&game, &player1, &SOLDIER), Err(Ok(TcgError::GameEnded)));
+    assert_eq!(client.try_end_turn(&game, &player1), Err(Ok(TcgError::GameEnded)));
+    assert_eq!(client.try_draw_card(&game, &player2, &0, &Bytes::from_slice(&env, &[1])), Err(Ok(TcgError::GameEnded)));
+    assert_eq!(
+        client.try_commit_deck(&game, &player2, &BytesN::from_array(&env, &[3; 32])),
+        Err(Ok(TcgError::GameEnded))
+    );
+    assert_eq!(client.try_concede(&game, &player1), Err(Ok(TcgError::GameEnded)));
+    assert_eq!(client.get_game(&game).winner, Some(player1));
+}
+
+#[test]
+fn test_concede_names_the_opponent() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup(&env);
+    let outsider = Address::generate(&env);
+
+    assert_eq!(client.try_concede(&game, &outsider), Err(Ok(TcgError::InvalidPlayer)));
+    client.concede(&game, &player2);
+
+    let over: Vec<GameOverEvent> = events_named(&env, "game_over");
+    assert_eq!(over, vec![&env, GameOverEvent { winner: player1, reason: GameOverReason::Conceded }]);
+}
+
+#[test]
+fn test_game_hub_hears_about_starts_and_results() {
+    let env = Env::default();
+    let (client, _, player1, player2) = setup(&env);
+    let hub = MockGameHubClient::new(&env, &env.register(MockGameHub, ()));
+    client.set_game_hub(&hub.address);
+
+    let game = client.init_game(&player1, &player2, &TcgRules::standard());
+    let session_id = client.get_hub_session(&game).unwrap();
+    let session = hub.get_session(&session_id).unwrap();
+    assert_eq!((session.game_id, session.player1, session.player1_won), (client.address.clone(), player1.clone(), None));
+
+    client.concede(&game, &player1);
+    assert_eq!(hub.get_session(&session_id).unwrap().player1_won, Some(false));
+}
+
+#[test]
+fn test_failing_game_hub_is_reported_not_fatal() {
+    let env = Env::default();
+    let (client, _, player1, player2) = setup(&env);
+    let hub = MockGameHubClient::new(&env, &env.register(MockGameHub, ()));
+    client.set_game_hub(&hub.address);
+    hub.set_failing(&true);
+
+    let game = client.init_game(&player1, &player2, &TcgRules::standard());
+    let failed: Vec<HubCallFailedEvent> = events_named(&env, "hub_fail");
+    assert_eq!(failed, vec![&env, HubCallFailedEvent { call: Symbol::new(&env, "start") }]);
+
+    // The hub never took the session, so the game keeps none and reports no result
+    assert_eq!(client.get_hub_session(&game), None);
+    client.concede(&game, &player2);
+    let failed: Vec<HubCallFailedEvent> = events_named(&env, "hub_fail");
+    assert_eq!(failed, vec![&env]);
+}
+
+// Starts a game on a mock hub where another game already holds session `taken`
+fn start_with_taken_session(env: &Env, taken: u32) -> (ZkTcgClient<'_>, MockGameHubClient<'_>, u32) {
+    let (client, _, player1, player2) = setup(env);
+    let hub = MockGameHubClient::new(env, &env.register(MockGameHub, ()));
+    hub.start_game(&Address::generate(env), &taken, &Address::generate(env), &Address::generate(env), &0, &0);
+    client.set_game_hub(&hub.address);
+
+    let game = client.init_game(&player1, &player2, &TcgRules::standard());
+    (client, hub, game)
+}
+
+#[test]
+fn test_a_taken_hub_session_id_is_drawn_again() {
+    // The same calls on a fresh Env draw the same ids, so take the first one
+    let first_draw = {
+        let env = Env::default();
+        let (client, _, game) = start_with_taken_session(&env, 0);
+        client.get_hub_session(&game).unwrap()
+    };
+    let env = Env::default();
+    let (client, hub, game) = start_with_taken_session(&env, first_draw);
+
+    let session_id = client.get_hub_session(&game).unwrap();
+    assert_ne!(session_id, first_draw);
+    assert_eq!(hub.get_session(&session_id).unwrap().game_id, client.address);
+    let failed: Vec<HubCallFailedEvent> = events_named(&env, "hub_fail");
+    assert_eq!(failed, vec![&env]);
+}
+
+#[test]
+fn test_timeout_can_be_claimed_after_the_deadline() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup(&env);
+    let deadline = client.get_game(&game).turn_deadline;
+    assert_eq!(deadline, env.ledger().sequence() + TcgRules::standard().turn_timeout);
+
+    assert_eq!(client.try_claim_timeout(&game, &player2), Err(Ok(TcgError::TimeoutNotReached)));
+    env.ledger().with_mut(|ledger| ledger.sequence_number = deadline + 1);
+    // Only the player waiting on the turn can claim it
+    assert_eq!(client.try_claim_timeout(&game, &player1), Err(Ok(TcgError::NotYourTurn)));
+
+    client.claim_timeout(&game, &player2);
+
+    let view = client.get_game(&game);
+    assert_eq!(view.phase, GamePhase::Ended);
+    assert_eq!(view.winner, Some(player2));
+}
+
+#[test]
+fn test_maximal_timeout_and_trap_duration_saturate() {
+    let env = Env::default();
+    let rules = TcgRules { turn_timeout: u32::MAX, trap_duration: u32::MAX, ..TcgRules::standard() };
+    let (client, game, player1, _) = setup_with_rules(&env, rules);
+    assert_eq!(client.get_game(&game).turn_deadline, u32::MAX);
+
+    draw(&env, &client, game, &player1, 1);
+    let salt = BytesN::from_array(&env, &[7; 32]);
+    let snare = client.set_trap(&game, &player1, &trap_commitment(&env, SNARE, &salt));
+    assert_eq!(client.get_traps(&game, &player1).get(snare).unwrap().expires_turn, u32::MAX);
+
+    client.end_turn(&game, &player1);
+    assert_eq!(client.get_game(&game).turn_deadline, u32::MAX);
+    env.ledger().with_mut(|ledger| ledger.sequence_number += TcgRules::standard().turn_timeout + 1);
+    assert_eq!(client.try_claim_timeout(&game, &player1), Err(Ok(TcgError::TimeoutNotReached)));
+}
+
+#[test]
+fn test_the_turn_clock_starts_with_the_second_deck() {
+    let env = Env::default();
+    env.mock_all_auths();
+    let client = ZkTcgClient::new(&env, &env.register(ZkTcg, ()));
+    client.init(&Address::generate(&env), &dummy_vk(&env));
+    let (player1, player2) = (Address::generate(&env), Address::generate(&env));
+    let game = client.init_game(&player1, &player2, &TcgRules::standard());
+    client.commit_deck(&game, &player1, &BytesN::from_array(&env, &[1; 32]));
+
+    // Player 2 never committing a deck is not a stalled turn
+    env.ledger().with_mut(|ledger| ledger.sequence_number += 10 * TcgRules::standard().turn_timeout);
+    assert_eq!(client.get_game(&game).turn_deadline, 0);
+    assert_eq!(client.try_claim_timeout(&game, &player2), Err(Ok(TcgError::DecksNotCommitted)));
+
+    client.commit_deck(&game, &player2, &BytesN::from_array(&env, &[2; 32]));
+    let view = client.get_game(&game);
+    assert_eq!(view.turn_deadline, env.ledger().sequence() + TcgRules::standard().turn_timeout);
+    assert_eq!(view.player1.mana, 1);
+    assert_eq!(client.try_claim_timeout(&game, &player2), Err(Ok(TcgError::TimeoutNotReached)));
+}
+
+#[test]
+fn test_ending_a_turn_restarts_the_clock() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup(&env);
+    let deadline = client.get_game(&game).turn_deadline;
+
+    env.ledger().with_mut(|ledger| ledger.sequence_number = deadline);
+    pass_turn(&env, &client, game, &player1);
+
+    assert_eq!(client.get_game(&game).turn_deadline, deadline + TcgRules::standard().turn_timeout);
+    env.ledger().with_mut(|ledger| ledger.sequence_number = deadline + 1);
+    assert_eq!(client.try_claim_timeout(&game, &player1), Err(Ok(TcgError::TimeoutNotReached)));
+    assert_eq!(client.try_claim_timeout(&game, &player2), Err(Ok(TcgError::NotYourTurn)));
+}
+
+#[test]
+fn test_creature_ids_survive_deaths_on_the_board() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup(&env);
+    let first = client.play_creature(&game, &player1, &SOLDIER);
+    let second = client.play_creature(&game, &player1, &SOLDIER);
+    pass_turn(&env, &client, game, &player1);
+    let theirs = client.play_creature(&game, &player2, &SOLDIER);
+    pass_turn(&env, &client, game, &player2);
+
+    // Losing the first creature must not shift the second one's id
+    client.attack_creature(&game, &player1, &first, &player2, &theirs);
+    client.attack(&game, &player1, &second, &player2);
+
+    assert_eq!((first, second, theirs), (1, 2, 3));
+    assert_eq!(client.get_board(&game, &player1), map![&env, (second, creature(SOLDIER, 2, 2, CreatureStatus::Exhausted))]);
+    assert_eq!(client.try_attack(&game, &player1, &first, &player2), Err(Ok(TcgError::InvalidTarget)));
+    // Ids keep counting up rather than refilling freed slots
+    assert_eq!(client.play_creature(&game, &player1, &SOLDIER), 4);
+}
+
+#[test]
+fn test_attack_creature_rejects_unknown_ids_and_own_board() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup(&env);
+    let first = client.play_creature(&game, &player1, &SOLDIER);
+    let second = client.play_creature(&game, &player1, &SOLDIER);
+    pass_turn(&env, &client, game, &player1);
+    let theirs = client.play_creature(&game, &player2, &SOLDIER);
+    pass_turn(&env, &client, game, &player2);
+
+    assert_eq!(client.try_attack_creature(&game, &player1, &first, &player2, &99), Err(Ok(TcgError::InvalidTarget)));
+    assert_eq!(client.try_attack_creature(&game, &player1, &theirs, &player2, &theirs), Err(Ok(TcgError::InvalidTarget)));
+    assert_eq!(client.try_attack_creature(&game, &player1, &first, &player1, &second), Err(Ok(TcgError::InvalidTarget)));
+}
+
+#[test]
+fn test_dead_creatures_and_spent_spells_fill_the_graveyard() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup(&env);
+    let mine = client.play_creature(&game, &player1, &SOLDIER);
+    pass_turn(&env, &client, game, &player1);
+    let theirs = client.play_creature(&game, &player2, &SOLDIER);
+    pass_turn(&env, &client, game, &player2);
+
+    client.attack_creature(&game, &player1, &mine, &player2, &theirs);
+    let buried: Vec<GraveyardEvent> = events_named(&env, "buried");
+    assert_eq!(
+        buried,
+        vec![
+            &env,
+            GraveyardEvent { owner: player1.clone(), card_id: SOLDIER, graveyard_size: 1 },
+            GraveyardEvent { owner: player2.clone(), card_id: SOLDIER, graveyard_size: 1 },
+        ]
+    );
+
+    client.play_spell(&game, &player1, &FIREBALL, &Target::Player(player2.clone()));
+
+    assert_eq!(client.get_graveyard(&game, &player1), vec![&env, SOLDIER, FIREBALL]);
+    let view = client.get_game(&game);
+    assert_eq!(view.player2.graveyard, vec![&env, SOLDIER]);
+}
+
+#[test]
+fn test_resurrect_returns_the_last_dead_creature() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup(&env);
+    let soldier = client.play_creature(&game, &player1, &SOLDIER);
+    pass_turn(&env, &client, game, &player1);
+    client.play_spell(&game, &player2, &SMITE, &Target::Creature(player1.clone(), soldier));
+    pass_turn(&env, &client, game, &player2);
+    let knight = client.play_creature(&game, &player1, &KNIGHT);
+    pass_turn(&env, &client, game, &player1);
+    client.play_spell(&game, &player2, &SMITE, &Target::Creature(player1.clone(), knight));
+    pass_turn(&env, &client, game, &player2);
+
+    // The spell buried after the knight is skipped over
+    client.play_spell(&game, &player1, &FIREBALL, &Target::Player(player2.clone()));
+    client.play_spell(&game, &player1, &REVIVE, &Target::None);
+
+    let exhumed: Vec<GraveyardEvent> = events_named(&env, "exhumed");
+    assert_eq!(exhumed, vec![&env, GraveyardEvent { owner: player1.clone(), card_id: KNIGHT, graveyard_size: 2 }]);
+    assert_eq!(client.get_board(&game, &player1), map![&env, (3, creature(KNIGHT, 3, 3, CreatureStatus::Sleeping))]);
+    assert_eq!(client.get_graveyard(&game, &player1), vec![&env, SOLDIER, FIREBALL, REVIVE]);
+}
+
+#[test]
+fn test_resurrect_needs_a_dead_creature() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup(&env);
+    client.play_spell(&game, &player1, &FIREBALL, &Target::Player(player2.clone()));
+
+    assert_eq!(client.try_play_spell(&game, &player1, &REVIVE, &Target::None), Err(Ok(TcgError::GraveyardEmpty)));
+    assert_eq!(client.get_graveyard(&game, &player1), vec![&env, FIREBALL]);
+}
+
+// Player 1 sets a snare on turn 1; player 2's soldier attacks player 1 on turn 4
+fn setup_sprung_snare(env: &Env) -> (ZkTcgClient<'_>, u32, Address, Address, u32, u32) {
+    let (client, game, player1, player2) = setup(env);
+    let salt = BytesN::from_array(env, &[7; 32]);
+    let snare = client.set_trap(&game, &player1, &trap_commitment(env, SNARE, &salt));
+    pass_turn(env, &client, game, &player1);
+    let soldier = client.play_creature(&game, &player2, &SOLDIER);
+    pass_turn(env, &client, game, &player2);
+    pass_turn(env, &client, game, &player1);
+    client.attack(&game, &player2, &soldier, &player1);
+    (client, game, player1, player2, snare, soldier)
+}
+
+// Twelve SOLDIERs committed the way the frontend commits a deck: Poseidon2 over the cards and a salt
+fn soldier_deck_hash(env: &Env) -> BytesN<32> {
+    let mut inputs = [poseidon2::Fr::from_u64(SOLDIER as u64); 13];
+    inputs[12] = poseidon2::Fr::from_u64(42);
+    BytesN::from_array(env, &poseidon2::hash(&inputs).to_be_bytes())
+}
+
+#[test]
+#[ignore = "check_deck_proof accepts any non-empty proof until the deck circuit has an on-chain verifier"]
+fn test_trap_from_outside_the_deck_is_rejected() {
+    let env = Env::default();
+    let deck2 = BytesN::from_array(&env, &[2; 32]);
+    let (client, game, player1, player2) = setup_with_decks(&env, TcgRules::standard(), &soldier_deck_hash(&env), &deck2);
+    draw(&env, &client, game, &player1, 5);
+    let salt = BytesN::from_array(&env, &[7; 32]);
+    let snare = client.set_trap(&game, &player1, &trap_commitment(&env, SNARE, &salt));
+    pass_turn(&env, &client, game, &player1);
+    let soldier = client.play_creature(&game, &player2, &SOLDIER);
+    pass_turn(&env, &client, game, &player2);
+    pass_turn(&env, &client, game, &player1);
+    client.attack(&game, &player2, &soldier, &player1);
+
+    // The deck holds no SNARE, so no proof can place one in it
+    assert_eq!(
+        client.try_reveal_trap(&game, &player1, &snare, &SNARE, &salt, &Bytes::from_slice(&env, &[1])),
+        Err(Ok(TcgError::InvalidProof))
+    );
+}
+
+#[test]
+fn test_revealed_trap_answers_the_attacker() {
+    let env = Env::default();
+    let (client, game, player1, player2, snare, soldier) = setup_sprung_snare(&env);
+    assert_eq!(client.get_state(&game), (13, 15));
+
+    let salt = BytesN::from_array(&env, &[7; 32]);
+    client.reveal_trap(&game, &player1, &snare, &SNARE, &salt, &Bytes::from_slice(&env, &[1]));
+
+    let sprung: Vec<TrapRevealedEvent> = events_named(&env, "sprung");
+    assert_eq!(
+        sprung,
+        vec![
+            &env,
+            TrapRevealedEvent { owner: player1.clone(), trap_id: snare, card_id: SNARE, trigger: Target::Creature(player2.clone(), soldier) }
+        ]
+    );
+    assert_eq!(client.get_state(&game), (14, 15));
+    assert_eq!(client.get_board(&game, &player2), Map::new(&env));
+    assert_eq!(client.get_traps(&game, &player1), Map::new(&env));
+    assert_eq!(client.get_graveyard(&game, &player1), vec![&env, SNARE]);
+    // Five drawn, one set as the trap and one drawn on turn 3
+    assert_eq!(client.get_game(&game).player1.hand_size, 5);
+}
+
+#[test]
+fn test_trap_reveal_checks_trigger_commitment_and_proof() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup_with_rules(&env, TcgRules { trap_duration: 10, ..TcgRules::standard() });
+    draw(&env, &client, game, &player1, 5);
+    let salt = BytesN::from_array(&env, &[7; 32]);
+    let proof = Bytes::from_slice(&env, &[1]);
+    let snare = client.set_trap(&game, &player1, &trap_commitment(&env, SNARE, &salt));
+    let bluff = client.set_trap(&game, &player1, &trap_commitment(&env, FIREBALL, &salt));
+
+    assert_eq!(client.try_reveal_trap(&game, &player1, &snare, &SNARE, &salt, &proof), Err(Ok(TcgError::TrapNotTriggered)));
+
+    pass_turn(&env, &client, game, &player1);
+    let soldier = client.play_creature(&game, &player2, &SOLDIER);
+    pass_turn(&env, &client, game, &player2);
+    pass_turn(&env, &client, game, &player1);
+    client.attack(&game, &player2, &soldier, &player1);
+
+    let wrong_salt = BytesN::from_array(&env, &[8; 32]);
+    assert_eq!(client.try_reveal_trap(&game, &player1, &snare, &SNARE, &wrong_salt, &proof), Err(Ok(TcgError::CommitmentMismatch)));
+    assert_eq!(client.try_reveal_trap(&game, &player1, &snare, &SNARE, &salt, &Bytes::new(&env)), Err(Ok(TcgError::InvalidProof)));
+    assert_eq!(client.try_reveal_trap(&game, &player1, &bluff, &FIREBALL, &salt, &proof), Err(Ok(TcgError::InvalidCard)));
+    assert_eq!(client.try_reveal_trap(&game, &player2, &snare, &SNARE, &salt, &proof), Err(Ok(TcgError::TrapNotFound)));
+
+    // The trigger goes stale once the turn after the attack is over
+    pass_turn(&env, &client, game, &player2);
+    pass_turn(&env, &client, game, &player1);
+    assert_eq!(client.try_reveal_trap(&game, &player1, &snare, &SNARE, &salt, &proof), Err(Ok(TcgError::TrapNotTriggered)));
+}
+
+#[test]
+fn test_trap_slots_are_limited() {
+    let env = Env::default();
+    let (client, game, player1, _) = setup(&env);
+    let commitment = trap_commitment(&env, SNARE, &BytesN::from_array(&env, &[7; 32]));
+    client.set_trap(&game, &player1, &commitment);
+    client.set_trap(&game, &player1, &commitment);
+
+    assert_eq!(client.try_set_trap(&game, &player1, &commitment), Err(Ok(TcgError::TrapSlotsFull)));
+}
+
+#[test]
+fn test_unrevealed_traps_expire() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup(&env);
+    let salt = BytesN::from_array(&env, &[7; 32]);
+    let snare = client.set_trap(&game, &player1, &trap_commitment(&env, SNARE, &salt));
+
+    // Set on turn 1 with the standard duration of 4, so it is gone when player 1's turn 7 starts
+    for _ in 0..2 {
+        pass_turn(&env, &client, game, &player1);
+        pass_turn(&env, &client, game, &player2);
+    }
+    pass_turn(&env, &client, game, &player1);
+    assert_eq!(client.get_traps(&game, &player1).len(), 1);
+    let soldier = client.play_creature(&game, &player2, &SOLDIER);
+    client.end_turn(&game, &player2);
+
+    let expired: Vec<TrapExpiredEvent> = events_named(&env, "trap_gone");
+    assert_eq!(expired, vec![&env, TrapExpiredEvent { owner: player1.clone(), trap_id: snare }]);
+    assert_eq!(client.get_traps(&game, &player1), Map::new(&env));
+    pass_turn(&env, &client, game, &player1);
+    client.attack(&game, &player2, &soldier, &player1);
+    assert_eq!(
+        client.try_reveal_trap(&game, &player1, &snare, &SNARE, &salt, &Bytes::from_slice(&env, &[1])),
+        Err(Ok(TcgError::TrapNotFound))
+    );
+}
+
+const DAY: u32 = 17_280;
+// What the contract extends its storage to
+const TTL_BUMP: u32 = 30 * DAY;
+
+#[test]
+fn test_moves_keep_the_game_alive() {
+    let env = Env::default();
+    let (client, game, player1, player2) = setup(&env);
+
+    // Each gap is far past the 4096-ledger default a fresh entry gets
+    for player in [&player1, &player2] {
+        env.ledger().with_mut(|ledger| ledger.sequence_number += 20 * DAY);
+        client.play_creature(&game, player, &SOLDIER);
+        pass_turn(&env, &client, game, player);
+    }
+
+    env.as_contract(&client.address, || {
+        assert_eq!(env.storage().persistent().get_ttl(&DataKey::Board(game, player1.clone())), TTL_BUMP);
+        assert_eq!(env.storage().persistent().get_ttl(&DataKey::DeckHash(game, player2.clone())), TTL_BUMP);
+        assert_eq!(env.storage().instance().get_ttl(), TTL_BUMP);
+    });
+    client.play_creature(&game, &player1, &SOLDIER);
+    assert_eq!(client.get_game(&game).turn_number, 3);
+}
+
+#[test]
+fn test_playing_a_card_keeps_it_registered() {
+    let env = Env::default();
+    let (client, game, player1, _) = setup(&env);
+
+    env.ledger().with_mut(|ledger| ledger.sequence_number += 20 * DAY);
+    client.play_creature(&game, &player1, &SOLDIER);
+
+    // Each card entry is extended when read, independently of the others
+    env.as_contract(&client.address, || {
+        assert_eq!(env.storage().persistent().get_ttl(&DataKey::Card(SOLDIER)), TTL_BUMP);
+        assert_eq!(env.storage().persistent().get_ttl(&DataKey::Card(GIANT)), TTL_BUMP - 20 * DAY);
+    });
+}
+
+#[test]
+fn test_admin_bump_ttl_keeps_an_idle_game_alive() {
+    let env = Env::default();
+    let (client, game, player1, _) = setup(&env);
+
+    env.ledger().with_mut(|ledger| ledger.sequence_number += 25 * DAY);
+    client.bump_ttl(&vec![&env, game], &vec![&env, SOLDIER]);
+    assert_eq!(client.try_bump_ttl(&vec![&env, 99], &vec![&env]), Err(Ok(TcgError::GameNotFound)));
+    assert_eq!(client.try_bump_ttl(&vec![&env], &vec![&env, 99]), Err(Ok(TcgError::CardNotFound)));
+
+    // Past the first 30 days, which the game alone would not have outlived
+    env.ledger().with_mut(|ledger| ledger.sequence_number += 25 * DAY);
+    client.play_creature(&game, &player1, &SOLDIER);
+    assert_eq!(client.get_board(&game, &player1).len(), 1);
+}
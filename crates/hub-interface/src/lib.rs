@@ -0,0 +1,23 @@
+#![no_std]
+
+// Types shared by the Game Hub and the games that report to it
+//
+// The hub's errors live here rather than in contracts/game-hub so the games can
+// match on them without linking the hub contract, and its exports, into their own.
+
+use soroban_sdk::contracterror;
+
+#[contracterror]
+#[derive(Copy, Clone, Debug, Eq, PartialEq, PartialOrd, Ord)]
+#[repr(u32)]
+pub enum HubError {
+    AlreadyInitialized = 1,
+    NotInitialized = 2,
+    GameNotRegistered = 3,
+    GameAlreadyRegistered = 4,
+    SessionExists = 5,
+    SessionNotFound = 6,
+    SessionEnded = 7,
+    InvalidPlayer = 8,
+    InvalidEloConfig = 9,
+}
@@ -0,0 +1,208 @@
+// BN254 scalar field (the Noir `Field` type)
+//
+// Elements are kept in Montgomery form as four little-endian 64-bit limbs. Every
+// conversion is a `const fn`, so the round constants are put in Montgomery form
+// at compile time.
+
+use core::ops::{Add, Mul};
+
+// p = 0x30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001
+const MODULUS: [u64; 4] = [0x43e1f593f0000001, 0x2833e84879b97091, 0xb85045b68181585d, 0x30644e72e131a029];
+
+// R^2 mod p, with R = 2^256
+const R2: [u64; 4] = [0x1bb8e645ae216da7, 0x53fe3ab1e35c59e3, 0x8c49833d53bb8085, 0x0216d0b17f4e44a5];
+
+// -p^-1 mod 2^64
+const INV: u64 = 0xc2e1f593efffffff;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Fr([u64; 4]);
+
+const fn mac(a: u64, b: u64, c: u64, carry: u64) -> (u64, u64) {
+    let t = a as u128 + (b as u128) * (c as u128) + carry as u128;
+    (t as u64, (t >> 64) as u64)
+}
+
+const fn adc(a: u64, b: u64, carry: u64) -> (u64, u64) {
+    let t = a as u128 + b as u128 + carry as u128;
+    (t as u64, (t >> 64) as u64)
+}
+
+const fn sbb(a: u64, b: u64, borrow: u64) -> (u64, u64) {
+    let t = (a as u128).wrapping_sub(b as u128 + borrow as u128);
+    (t as u64, (t >> 127) as u64)
+}
+
+// `limbs - p`, or `None` when that would go negative
+const fn sub_modulus(limbs: [u64; 4]) -> Option<[u64; 4]> {
+    let mut out = [0u64; 4];
+    let mut borrow = 0;
+    let mut i = 0;
+    while i < 4 {
+        let (v, b) = sbb(limbs[i], MODULUS[i], borrow);
+        out[i] = v;
+        borrow = b;
+        i += 1;
+    }
+    if borrow == 0 {
+        Some(out)
+    } else {
+        None
+    }
+}
+
+// Brings a value below 2p back into [0, p)
+const fn reduce_once(limbs: [u64; 4]) -> [u64; 4] {
+    match sub_modulus(limbs) {
+        Some(reduced) => reduced,
+        None => limbs,
+    }
+}
+
+const fn montgomery_reduce(mut r: [u64; 8]) -> [u64; 4] {
+    let mut carry2 = 0;
+    let mut i = 0;
+    while i < 4 {
+        let k = r[i].wrapping_mul(INV);
+        let mut carry = 0;
+        let mut j = 0;
+        while j < 4 {
+            let (v, c) = mac(r[i + j], k, MODULUS[j], carry);
+            r[i + j] = v;
+            carry = c;
+            j += 1;
+        }
+        let (v, c) = adc(r[i + 4], carry2, carry);
+        r[i + 4] = v;
+        carry2 = c;
+        i += 1;
+    }
+    reduce_once([r[4], r[5], r[6], r[7]])
+}
+
+const fn montgomery_mul(a: [u64; 4], b: [u64; 4]) -> [u64; 4] {
+    let mut r = [0u64; 8];
+    let mut i = 0;
+    while i < 4 {
+        let mut carry = 0;
+        let mut j = 0;
+        while j < 4 {
+            let (v, c) = mac(r[i + j], a[i], b[j], carry);
+            r[i + j] = v;
+            carry = c;
+            j += 1;
+        }
+        r[i + 4] = carry;
+        i += 1;
+    }
+    montgomery_reduce(r)
+}
+
+const fn hex_digit(c: u8) -> u64 {
+    match c {
+        b'0'..=b'9' => (c - b'0') as u64,
+        b'a'..=b'f' => (c - b'a' + 10) as u64,
+        b'A'..=b'F' => (c - b'A' + 10) as u64,
+        _ => panic!("invalid hex digit"),
+    }
+}
+
+impl Fr {
+    pub const ZERO: Fr = Fr([0; 4]);
+    pub const ONE: Fr = Fr::from_u64(1);
+
+    // Takes canonical little-endian limbs, which must already be below p
+    const fn from_canonical(limbs: [u64; 4]) -> Fr {
+        Fr(montgomery_mul(limbs, R2))
+    }
+
+    pub const fn from_u64(value: u64) -> Fr {
+        Fr::from_canonical([value, 0, 0, 0])
+    }
+
+    pub const fn from_u128(value: u128) -> Fr {
+        Fr::from_canonical([value as u64, (value >> 64) as u64, 0, 0])
+    }
+
+    // Parses exactly 64 big-endian hex digits; only meant for constants
+    pub(crate) const fn from_hex(hex: &str) -> Fr {
+        let bytes = hex.as_bytes();
+        assert!(bytes.len() == 64, "expected 64 hex digits");
+        let mut limbs = [0u64; 4];
+        let mut i = 0;
+        while i < 64 {
+            let limb = 3 - i / 16;
+            limbs[limb] = (limbs[limb] << 4) | hex_digit(bytes[i]);
+            i += 1;
+        }
+        assert!(sub_modulus(limbs).is_none(), "constant is not below the modulus");
+        Fr::from_canonical(limbs)
+    }
+
+    // Big-endian, the layout Noir and the contracts use for a `Field`.
+    // Returns `None` for encodings of p or above instead of reducing them.
+    pub const fn from_be_bytes(bytes: &[u8; 32]) -> Option<Fr> {
+        let mut limbs = [0u64; 4];
+        let mut i = 0;
+        while i < 32 {
+            let limb = 3 - i / 8;
+            limbs[limb] = (limbs[limb] << 8) | bytes[i] as u64;
+            i += 1;
+        }
+        match sub_modulus(limbs) {
+            Some(_) => None,
+            None => Some(Fr::from_canonical(limbs)),
+        }
+    }
+
+    pub const fn to_be_bytes(&self) -> [u8; 32] {
+        let limbs = montgomery_reduce([self.0[0], self.0[1], self.0[2], self.0[3], 0, 0, 0, 0]);
+        let mut out = [0u8; 32];
+        let mut i = 0;
+        while i < 32 {
+            out[i] = (limbs[3 - i / 8] >> (8 * (7 - i % 8))) as u8;
+            i += 1;
+        }
+        out
+    }
+
+    // x^5, the Poseidon2 S-box
+    pub(crate) fn pow5(self) -> Fr {
+        let square = self * self;
+        square * square * self
+    }
+}
+
+impl Default for Fr {
+    fn default() -> Self {
+        Fr::ZERO
+    }
+}
+
+impl From<u64> for Fr {
+    fn from(value: u64) -> Self {
+        Fr::from_u64(value)
+    }
+}
+
+impl Add for Fr {
+    type Output = Fr;
+
+    fn add(self, rhs: Fr) -> Fr {
+        // Both sides are below p < 2^254, so the sum cannot carry out of the top limb
+        let mut out = [0u64; 4];
+        let mut carry = 0;
+        for (i, limb) in out.iter_mut().enumerate() {
+            (*limb, carry) = adc(self.0[i], rhs.0[i], carry);
+        }
+        Fr(reduce_once(out))
+    }
+}
+
+impl Mul for Fr {
+    type Output = Fr;
+
+    fn mul(self, rhs: Fr) -> Fr {
+        Fr(montgomery_mul(self.0, rhs.0))
+    }
+}
@@ -0,0 +1,87 @@
+#![no_std]
+
+// Poseidon2 over BN254, bit-compatible with the Noir circuits
+//
+// `permutation` matches Noir's `std::hash::poseidon2_permutation(state, 4)`, and `hash`
+// is the rate-3 sponge the circuits unroll in `poseidon2_hash_26` (board || salt) and
+// `poseidon2_hash_13` (deck || salt). Pure Rust with no allocations, so it runs both
+// inside the Soroban contracts and in native tooling that prepares commitments.
+
+mod constants;
+mod field;
+
+pub use field::Fr;
+
+use constants::{INTERNAL_MATRIX_DIAGONAL, ROUND_CONSTANTS};
+
+pub const WIDTH: usize = 4;
+pub const RATE: usize = 3;
+
+const ROUNDS_F: usize = 8;
+const ROUNDS_P: usize = 56;
+
+// 2^64, the domain separator the circuits scale the input length by
+const IV_SHIFT: u128 = 1 << 64;
+
+// External (full round) matrix, barretenberg's 4x4 MDS layout
+fn external_matrix(state: &mut [Fr; WIDTH]) {
+    let t0 = state[0] + state[1];
+    let t1 = state[2] + state[3];
+    let t2 = state[1] + state[1] + t1;
+    let t3 = state[3] + state[3] + t0;
+    let t1_4 = t1 + t1 + t1 + t1;
+    let t0_4 = t0 + t0 + t0 + t0;
+    let t4 = t1_4 + t3;
+    let t5 = t0_4 + t2;
+    *state = [t3 + t5, t5, t2 + t4, t4];
+}
+
+// Internal (partial round) matrix: diag(d) + the all-ones matrix
+fn internal_matrix(state: &mut [Fr; WIDTH]) {
+    let sum = state[0] + state[1] + state[2] + state[3];
+    for (lane, diagonal) in state.iter_mut().zip(INTERNAL_MATRIX_DIAGONAL) {
+        *lane = *lane * diagonal + sum;
+    }
+}
+
+fn full_round(state: &mut [Fr; WIDTH], round: usize) {
+    for (lane, constant) in state.iter_mut().zip(ROUND_CONSTANTS[round]) {
+        *lane = (*lane + constant).pow5();
+    }
+    external_matrix(state);
+}
+
+fn partial_round(state: &mut [Fr; WIDTH], round: usize) {
+    state[0] = (state[0] + ROUND_CONSTANTS[round][0]).pow5();
+    internal_matrix(state);
+}
+
+pub fn permutation(mut state: [Fr; WIDTH]) -> [Fr; WIDTH] {
+    external_matrix(&mut state);
+    for round in 0..ROUNDS_F / 2 {
+        full_round(&mut state, round);
+    }
+    for round in ROUNDS_F / 2..ROUNDS_F / 2 + ROUNDS_P {
+        partial_round(&mut state, round);
+    }
+    for round in ROUNDS_F / 2 + ROUNDS_P..ROUNDS_F + ROUNDS_P {
+        full_round(&mut state, round);
+    }
+    state
+}
+
+// Sponge hash: the state starts as [0, 0, 0, len * 2^64], each chunk of up to
+// three inputs is added into the rate lanes and permuted, and lane 0 is squeezed
+pub fn hash(inputs: &[Fr]) -> Fr {
+    let mut state = [Fr::ZERO; WIDTH];
+    state[WIDTH - 1] = Fr::from_u128(inputs.len() as u128 * IV_SHIFT);
+    for chunk in inputs.chunks(RATE) {
+        for (lane, input) in state.iter_mut().zip(chunk) {
+            *lane = *lane + *input;
+        }
+        state = permutation(state);
+    }
+    state[0]
+}
+
+mod test;
@@ -0,0 +1,139 @@
+#![cfg(test)]
+
+use crate::{hash, permutation, Fr};
+
+fn fields<const N: usize>(hex: [&str; N]) -> [Fr; N] {
+    hex.map(Fr::from_hex)
+}
+
+// Reference vectors from Noir's `bn254_blackbox_solver` Poseidon2 tests
+#[test]
+fn test_permutation_matches_noir() {
+    let cases = [
+        (
+            [
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000000",
+            ],
+            [
+                "18DFB8DC9B82229CFF974EFEFC8DF78B1CE96D9D844236B496785C698BC6732E",
+                "095C230D1D37A246E8D2D5A63B165FE0FADE040D442F61E25F0590E5FB76F839",
+                "0BB9545846E1AFA4FA3C97414A60A20FC4949F537A68CCECA34C5CE71E28AA59",
+                "18A4F34C9C6F99335FF7638B82AEED9018026618358873C982BBDDE265B2ED6D",
+            ],
+        ),
+        (
+            [
+                "0000000000000000000000000000000000000000000000000000000000000000",
+                "0000000000000000000000000000000000000000000000000000000000000001",
+                "0000000000000000000000000000000000000000000000000000000000000002",
+                "0000000000000000000000000000000000000000000000000000000000000003",
+            ],
+            [
+                "01BD538C2EE014ED5141B29E9AE240BF8DB3FE5B9A38629A9647CF8D76C01737",
+                "239B62E7DB98AA3A2A8F6A0D2FA1709E7A35959AA6C7034814D9DAA90CBAC662",
+                "04CBB44C61D928ED06808456BF758CBF0C18D1E15A7B6DBC8245FA7515D5E3CB",
+                "2E11C5CFF2A22C64D01304B778D78F6998EFF1AB73163A35603F54794C30847A",
+            ],
+        ),
+        (
+            [
+                "00000000000000000000000000000000ffffffffffffffffffffffffffffffff",
+                "00000000000000000000000000000000ffffffffffffffffffffffffffffffff",
+                "00000000000000000000000000000000ffffffffffffffffffffffffffffffff",
+                "00000000000000000000000000000000ffffffffffffffffffffffffffffffff",
+            ],
+            [
+                "1452D1D69A606FB2F6AFF10FA4C73EA7486AC4BD59B3557B52311EFFB283A261",
+                "2433004A0EDE6798EF76B637F9E2A0EAB454D70B7433A9AB18512D5A980890A9",
+                "05A2ECD90756DD7DBD1840B0F252E490A73594CD103B56F6A3B1ADD8F38449BE",
+                "1D5B91141464C8B36F830F33B7BA06BEA37D309A7A5E63A91100BB23C55168F1",
+            ],
+        ),
+        (
+            [
+                "30644E72E131A029B85045B68181585D2833E84879B9709143E1F593F0000000",
+                "30644E72E131A029B85045B68181585D2833E84879B9709143E1F593F0000000",
+                "30644E72E131A029B85045B68181585D2833E84879B9709143E1F593F0000000",
+                "30644E72E131A029B85045B68181585D2833E84879B9709143E1F593F0000000",
+            ],
+            [
+                "1B18E6CA21A1E9B15D65F0B5861EDE5FF20DB8FA3722531823D0C817D69D945D",
+                "0AFB50EA6867B1CB2D9D1EAC935AF746BC7A780E181A1E6AE9B768C9CBA68878",
+                "0A521A22CA614E65B877D0676652FB60E90A11B462F9846A08E811D95272A9D8",
+                "2369F077784E0AEA99EE3DC6B7B01612AF7F80D7F08B755F9F116E2885EE367F",
+            ],
+        ),
+        (
+            [
+                "123456789ABCDEF00FEDCBA987654321123456789ABCDEF00FEDCBA987654321",
+                "2718281828459045235360287471352662497757247093699959574966967627",
+                "1414213562373095048801688724209698078569671875376948073176679737",
+                "0B172182839274F8E5D4C3B2A1908070605040302010FFEEDDCCBBAA99887766",
+            ],
+            [
+                "1C68B20A2080BCC11A2B6F38A46F8270C3CE1DCD40CF8A16626E1CC936E90D56",
+                "22FDAD6F2E2AED646BE444EFB2AE2EAACD49F0440C846F4882F8B013C01C792C",
+                "1726C0B52C59E7008DBB710A8D3046214257D997A6E7870F46E7DFE5C6729378",
+                "03B4A4B3B3694B4EFAF50186E75062F30D3FF4B73D95CAB554763B7E19DE8BA0",
+            ],
+        ),
+    ];
+
+    for (input, expected) in cases {
+        assert_eq!(permutation(fields(input)), fields(expected));
+    }
+}
+
+// The sponge digests below are regression values computed by this crate, not
+// by nargo: its permutation is pinned by the Noir vectors above, but the sponge
+// around it has not yet been checked against `poseidon2_hash_26`/`_13` output.
+
+// `test_board()` and salt 42 from circuits/src/main.nr
+#[test]
+fn test_hash_26_board_commitment() {
+    let mut input = [Fr::ZERO; 26];
+    for (cell, ship) in [(0, 1), (1, 1), (2, 1), (5, 2), (6, 2)] {
+        input[cell] = Fr::from_u64(ship);
+    }
+    input[25] = Fr::from_u64(42);
+
+    assert_eq!(
+        hash(&input),
+        Fr::from_hex("29933adbf818e4244b574b3fb5ab5fadb32e0ca401f4dc4921dc2ea76ac287ab")
+    );
+}
+
+// Deck and salt from `test_valid_draw` in zk_tcg_circuit/src/main.nr
+#[test]
+fn test_hash_13_deck_commitment() {
+    let input = [1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 4, 4, 999].map(Fr::from_u64);
+
+    assert_eq!(
+        hash(&input),
+        Fr::from_hex("166d0896a2045105aa7482934f9a9fe49428a0d6dc030cbb9419fb244a743fe0")
+    );
+}
+
+#[test]
+fn test_bytes_round_trip() {
+    let value = Fr::from_hex("2718281828459045235360287471352662497757247093699959574966967627");
+    let bytes = value.to_be_bytes();
+
+    assert_eq!(bytes[0], 0x27);
+    assert_eq!(bytes[31], 0x27);
+    assert_eq!(Fr::from_be_bytes(&bytes), Some(value));
+    assert_eq!(Fr::from_u64(42).to_be_bytes()[31], 42);
+}
+
+#[test]
+fn test_from_be_bytes_rejects_non_canonical() {
+    let mut modulus = Fr::from_hex("30644E72E131A029B85045B68181585D2833E84879B9709143E1F593F0000000").to_be_bytes();
+    assert!(Fr::from_be_bytes(&modulus).is_some());
+
+    modulus[31] += 1;
+    assert_eq!(Fr::from_be_bytes(&modulus), None);
+    assert_eq!(Fr::from_be_bytes(&[0xff; 32]), None);
+}